@@ -1,11 +1,12 @@
-use crate::StdRng;
-use alloc::string::String;
-use pincerhex_core::{first_move, Colour, Move, PieceState, PotentialEvaluator, Tile, TileError};
-use pincerhex_state::{Error as StateError, State, Winner, DEFAULT_SIZE};
-
-/// Whether or not to play with the swap rule
-/// Should probably be an environment variable
-pub const SWAP_RULE: bool = true;
+use crate::{EngineKind, SeededRng};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use pincerhex_core::{
+    first_move, BoardError, Colour, Deadline, Engine, EvalParams, Evaluation, Move, PieceState,
+    Rand, Tile, TileError, MAX_SIZE,
+};
+use pincerhex_state::{Error as StateError, State, SwapKind, Winner, DEFAULT_SIZE};
+use rand::Rng;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Copy)]
 pub enum SwapRole {
@@ -22,6 +23,17 @@ impl From<Colour> for SwapRole {
     }
 }
 
+/// Lowest number of moves the remaining clock time is split between
+const MIN_MOVES_LEFT: u32 = 8;
+
+struct Until(Instant);
+
+impl Deadline for Until {
+    fn expired(&self) -> bool {
+        Instant::now() >= self.0
+    }
+}
+
 pub struct HexBot {
     colour: Colour,
     starting: Colour,
@@ -30,7 +42,17 @@ pub struct HexBot {
     #[allow(dead_code)]
     allow_invalid: bool,
     swap_state: Option<SwapRole>,
-    move_count: u16,
+    swap_rule: bool,
+    swap_kind: SwapKind,
+    /// Stones the bot placed itself, to know when the swap decision is open again
+    own_moves: u16,
+    engine: Box<dyn Engine>,
+    engine_kind: EngineKind,
+    params: EvalParams,
+    time_per_move: Option<Duration>,
+    rng: Box<dyn Rand>,
+    /// Seed of `rng`, unless it was injected
+    seed: Option<u64>,
 }
 
 #[derive(Debug)]
@@ -38,6 +60,8 @@ pub enum BotError {
     State(StateError),
     EmptyMove,
     InvalidMove(TileError),
+    NothingToUndo,
+    GameOver,
 }
 
 impl core::fmt::Display for BotError {
@@ -46,6 +70,8 @@ impl core::fmt::Display for BotError {
             Self::State(e) => write!(f, "{e}"),
             Self::InvalidMove(m) => write!(f, "{m}"),
             Self::EmptyMove => write!(f, "empty move"),
+            Self::NothingToUndo => write!(f, "nothing to undo"),
+            Self::GameOver => write!(f, "the game is over"),
         }
     }
 }
@@ -57,8 +83,24 @@ impl From<StateError> for BotError {
 }
 
 impl HexBot {
+    /// Creates a bot with a random seed
     #[must_use]
     pub fn new(c: Colour) -> Self {
+        Self::with_seed(c, rand::thread_rng().gen())
+    }
+
+    /// Creates a bot whose games are reproducible from `seed`. The RNG is reseeded on every
+    /// `init_board`.
+    #[must_use]
+    pub fn with_seed(c: Colour, seed: u64) -> Self {
+        let mut bot = Self::with_rng(c, Box::new(SeededRng::new(seed)));
+        bot.seed = Some(seed);
+        bot
+    }
+
+    /// Creates a bot that draws all of its randomness from `rng`
+    #[must_use]
+    pub fn with_rng(c: Colour, rng: Box<dyn Rand>) -> Self {
         Self {
             colour: c,
             starting: c,
@@ -66,15 +108,43 @@ impl HexBot {
             size: DEFAULT_SIZE,
             allow_invalid: true,
             swap_state: Some(SwapRole::from(c)),
-            move_count: 0,
+            swap_rule: true,
+            swap_kind: SwapKind::Sides,
+            own_moves: 0,
+            engine: EngineKind::Potential.engine(EvalParams::DEFAULT),
+            engine_kind: EngineKind::Potential,
+            params: EvalParams::DEFAULT,
+            time_per_move: None,
+            rng,
+            seed: None,
         }
     }
 
+    #[must_use]
+    pub const fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
     #[must_use]
     pub const fn colour(&self) -> Colour {
         self.colour
     }
 
+    pub fn set_engine(&mut self, engine: EngineKind) {
+        self.engine_kind = engine;
+        self.engine = engine.engine(self.params);
+    }
+
+    pub fn set_eval_params(&mut self, params: EvalParams) {
+        self.params = params;
+        self.engine = self.engine_kind.engine(params);
+    }
+
+    /// Sets how long `make_move` may search for. With `None` the engine uses its own budget.
+    pub const fn set_time_per_move(&mut self, time: Option<Duration>) {
+        self.time_per_move = time;
+    }
+
     fn place_piece(&mut self, mv: Tile, state: PieceState) -> Result<(), BotError> {
         self.state.place_piece(mv, state).map_err(BotError::State)
     }
@@ -87,11 +157,51 @@ impl HexBot {
             .and_then(|mv| self.place_piece(mv, state))
     }
 
-    pub fn init_board(&mut self, size: i8) {
-        self.state = State::new(size);
+    /// Sets whether the swap rule is used, starting from the next `init_board`
+    pub const fn set_swap_rule(&mut self, swap_rule: bool) {
+        self.swap_rule = swap_rule;
+    }
+
+    /// Sets how swaps are played, starting from the next `init_board`
+    pub const fn set_swap_kind(&mut self, swap_kind: SwapKind) {
+        self.swap_kind = swap_kind;
+    }
+
+    /// # Errors
+    /// Will return an `Err` if `size` is not between 1 and `MAX_SIZE`
+    pub fn init_board(&mut self, size: i8) -> Result<(), BotError> {
+        if !(1..=MAX_SIZE).contains(&size) {
+            return Err(BotError::State(StateError::Board(BoardError::NotInRange)));
+        }
+        self.state = State::with_swap_rule(size, self.swap_rule);
+        self.state.set_swap_kind(self.swap_kind);
         self.size = size;
         self.swap_state = Some(SwapRole::from(self.colour));
-        self.move_count = 0;
+        self.own_moves = 0;
+        if let Some(seed) = self.seed {
+            self.rng = Box::new(SeededRng::new(seed));
+        }
+        Ok(())
+    }
+
+    #[must_use]
+    pub const fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// Continues the game in `state`, keeping the bot's colour
+    pub fn load_state(&mut self, state: State) {
+        let own = PieceState::Colour(self.colour);
+        self.size = state.get_board().size;
+        self.swap_rule = state.swap_rule();
+        self.swap_kind = state.swap_kind();
+        self.own_moves = state.history().iter().filter(|e| e.piece == own).count() as u16;
+        self.swap_state = match state.history() {
+            [] => Some(SwapRole::from(self.colour)),
+            [_] if state.active() == self.colour => Some(SwapRole::Swap),
+            _ => None,
+        };
+        self.state = state;
     }
 
     #[must_use]
@@ -107,55 +217,205 @@ impl HexBot {
     /// # Errors
     /// Will return an `Err` if applying the swap rule failed
     pub fn make_move(&mut self) -> Result<Move, BotError> {
-        if let (Some(s), true) = (self.swap_state, SWAP_RULE) {
-            let mv = self.handle_swap(s)?;
+        match self.time_per_move {
+            Some(time) => self.make_move_until(Instant::now() + time),
+            None => self.play(None),
+        }
+    }
+
+    /// Same as `make_move` but searches until `deadline`
+    ///
+    /// # Errors
+    /// Will return an `Err` if applying the swap rule failed
+    pub fn make_move_until(&mut self, deadline: Instant) -> Result<Move, BotError> {
+        self.play(Some(&Until(deadline)))
+    }
+
+    /// Same as `make_move` but budgets the search time from the `remaining` time on the game
+    /// clock, assuming the game lasts until the board is full.
+    ///
+    /// # Errors
+    /// Will return an `Err` if applying the swap rule failed
+    pub fn make_move_with_clock(&mut self, remaining: Duration) -> Result<Move, BotError> {
+        let empty = self
+            .state
+            .get_board()
+            .iter()
+            .filter(|(_, s)| *s == PieceState::Empty)
+            .count() as u32;
+        let moves_left = (empty / 2).max(MIN_MOVES_LEFT);
+        self.make_move_until(Instant::now() + remaining / moves_left)
+    }
+
+    fn play(&mut self, deadline: Option<&dyn Deadline>) -> Result<Move, BotError> {
+        if let (Some(s), true) = (self.swap_state, self.state.swap_rule()) {
+            let mv = self.handle_swap(s, deadline)?;
             self.swap_state = None;
             Ok(mv)
         } else {
-            Ok(Move::Move(self.regular_move()))
+            Ok(Move::Move(self.regular_move(deadline)))
         }
     }
 
-    fn handle_swap(&mut self, s: SwapRole) -> Result<Move, BotError> {
-        let mut rng = StdRng(rand::thread_rng());
+    fn handle_swap(
+        &mut self,
+        s: SwapRole,
+        deadline: Option<&dyn Deadline>,
+    ) -> Result<Move, BotError> {
         match s {
             SwapRole::Start => {
-                let (i, j) = first_move(self.size, &mut rng);
+                let (i, j) = first_move(self.size, self.rng.as_mut());
                 let mv = Tile::Regular(i, j);
                 self.state
                     .place_piece(mv, PieceState::Colour(self.colour))?;
-                self.move_count += 1;
+                self.own_moves += 1;
                 Ok(Move::Move(mv))
             }
             SwapRole::Swap => {
-                if self.state.should_swap(&mut rng) {
-                    self.swap();
+                if self.state.should_swap(self.rng.as_mut()) {
+                    self.swap()?;
                     Ok(Move::Swap)
                 } else {
-                    Ok(Move::Move(self.regular_move()))
+                    Ok(Move::Move(self.regular_move(deadline)))
                 }
             }
         }
     }
 
-    fn regular_move(&mut self) -> Tile {
-        let mut rng = StdRng(rand::thread_rng());
-        let (i, j) = PotentialEvaluator::new(self.state.get_board(), self.colour, self.starting)
-            .evaluate()
-            .get_best_move(self.move_count, &mut rng);
-        let mv = Tile::Regular(i, j);
+    /// Searches the current position for the bot's colour without playing the move
+    ///
+    /// # Errors
+    /// Will return an `Err` if the game is over or there are no empty tiles left
+    pub fn analyze(&mut self) -> Result<Evaluation, BotError> {
+        if self.state.check_win().is_some() || self.state.bitboard().is_full() {
+            return Err(BotError::GameOver);
+        }
+        let until = self.time_per_move.map(|time| Until(Instant::now() + time));
+        Ok(self.search(until.as_ref().map(|u| u as &dyn Deadline)))
+    }
 
+    fn search(&mut self, deadline: Option<&dyn Deadline>) -> Evaluation {
+        let board = self.state.get_board();
+        let move_count = self.state.move_count();
+        match deadline {
+            Some(deadline) => self.engine.best_move_until(
+                board,
+                self.colour,
+                self.starting,
+                move_count,
+                self.rng.as_mut(),
+                deadline,
+            ),
+            None => self.engine.best_move(
+                board,
+                self.colour,
+                self.starting,
+                move_count,
+                self.rng.as_mut(),
+            ),
+        }
+    }
+
+    fn regular_move(&mut self, deadline: Option<&dyn Deadline>) -> Tile {
+        let mv = self.search(deadline).mv;
         self.place_piece(mv, PieceState::Colour(self.colour))
             .expect("valid move");
-        self.move_count += 1;
+        self.own_moves += 1;
         mv
     }
 
-    pub fn check_win(&mut self) -> Option<Winner> {
+    #[must_use]
+    pub fn check_win(&self) -> Option<Winner> {
         self.state.get_winner(self.colour)
     }
 
-    pub fn swap(&mut self) {
-        self.colour = self.colour.opponent();
+    /// Shortest chain of stones that won the game, if it is over
+    #[must_use]
+    pub fn winning_path(&self) -> Option<Vec<Tile>> {
+        self.state.winning_path().map(|(_, path)| path)
+    }
+
+    /// Plays the swap rule. The bot only changes colour when the players swap sides.
+    ///
+    /// # Errors
+    /// Will return an `Err` if the state is strict and the swap is illegal
+    pub fn swap(&mut self) -> Result<(), BotError> {
+        self.state.swap()?;
+        if self.state.swap_kind() == SwapKind::Sides {
+            self.colour = self.colour.opponent();
+        }
+        Ok(())
+    }
+
+    /// Takes back the last move, whoever played it
+    ///
+    /// # Errors
+    /// Will return an `Err` if no moves were played
+    pub fn undo(&mut self) -> Result<(), BotError> {
+        let entry = self.state.undo().ok_or(BotError::NothingToUndo)?;
+        match entry.mv {
+            Move::Swap => {
+                if entry.swap_kind() == Some(SwapKind::Sides) {
+                    self.colour = self.colour.opponent();
+                }
+            }
+            Move::Move(_) => {
+                if entry.piece == PieceState::Colour(self.colour) {
+                    self.own_moves = self.own_moves.saturating_sub(1);
+                }
+            }
+        }
+        // The swap decision is open again once the bot's first move is taken back
+        let swapped = self.state.history().iter().any(|e| e.mv == Move::Swap);
+        if self.own_moves == 0 && !swapped {
+            self.swap_state = Some(SwapRole::from(self.colour));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn play_game(seed: u64) -> String {
+        let mut bot = HexBot::with_seed(Colour::Black, seed);
+        bot.init_board(6).unwrap();
+        for _ in 0..4 {
+            bot.make_move().unwrap();
+        }
+        bot.get_compressed()
+    }
+
+    #[test]
+    fn seeded_games_are_reproducible() {
+        assert_eq!(play_game(42), play_game(42));
+    }
+
+    #[test]
+    fn undo_swap() {
+        let mut bot = HexBot::with_seed(Colour::White, 1);
+        bot.init_board(5).unwrap();
+        bot.set_tile(Some(&"b2"), PieceState::Colour(Colour::Black))
+            .unwrap();
+        bot.swap().unwrap();
+        assert_eq!(bot.colour(), Colour::Black);
+
+        bot.undo().unwrap();
+        assert_eq!(bot.colour(), Colour::White);
+        bot.undo().unwrap();
+        assert!(matches!(bot.undo(), Err(BotError::NothingToUndo)));
+        assert_eq!(bot.get_compressed(), ".....|.....|.....|.....|.....|");
+    }
+
+    #[test]
+    fn analyze_after_the_game() {
+        let mut bot = HexBot::with_seed(Colour::Black, 1);
+        assert!(bot.init_board(MAX_SIZE + 1).is_err());
+        bot.init_board(1).unwrap();
+        assert!(bot.analyze().is_ok());
+        bot.set_tile(Some(&"a1"), PieceState::Colour(Colour::Black))
+            .unwrap();
+        assert!(matches!(bot.analyze(), Err(BotError::GameOver)));
     }
 }
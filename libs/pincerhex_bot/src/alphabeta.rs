@@ -0,0 +1,354 @@
+use alloc::{vec, vec::Vec};
+use pincerhex_core::{
+    Board, Colour, Deadline, Engine, EvalParams, Evaluation, PieceState, PotentialEvaluator, Rand,
+    Tile,
+};
+use pincerhex_state::State;
+
+pub const DEFAULT_DEPTH: u8 = 2;
+pub const DEFAULT_WIDTH: usize = 8;
+pub const DEFAULT_MAX_NODES: u64 = 20_000;
+
+/// Score of a won position. Wins found closer to the root score higher.
+const WIN: i32 = 1_000_000;
+
+/// Depth limited negamax search with alpha-beta pruning. Positions are scored by the
+/// [`PotentialEvaluator`]'s potentials and moves are ordered by its move scores.
+pub struct AlphaBeta {
+    depth: u8,
+    /// Number of the best moves, as ranked by the evaluator, searched at every node
+    width: usize,
+    max_nodes: u64,
+    nodes: u64,
+    /// Set when the deadline expired in the middle of a search
+    aborted: bool,
+    params: EvalParams,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    /// Score from the point of view of the side to move. Higher is better.
+    pub score: i32,
+    /// Principal variation, starting with the best move
+    pub pv: Vec<Tile>,
+    /// Number of nodes searched
+    pub nodes: u64,
+}
+
+impl Default for AlphaBeta {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_DEPTH,
+            DEFAULT_WIDTH,
+            DEFAULT_MAX_NODES,
+            EvalParams::DEFAULT,
+        )
+    }
+}
+
+impl AlphaBeta {
+    #[must_use]
+    pub const fn new(depth: u8, width: usize, max_nodes: u64, params: EvalParams) -> Self {
+        Self {
+            depth,
+            width,
+            max_nodes,
+            nodes: 0,
+            aborted: false,
+            params,
+        }
+    }
+
+    /// Searches the position for `active`. Once the node budget runs out the remaining nodes
+    /// are scored statically.
+    ///
+    /// # Panics
+    /// Panics if there are no empty tiles on the board
+    pub fn search(
+        &mut self,
+        board: &Board,
+        active: Colour,
+        starting: Colour,
+        move_count: u16,
+        rng: &mut (impl Rand + ?Sized),
+    ) -> SearchResult {
+        let mut state = State::from(board.clone());
+        state.set_to_play(active);
+        self.run(
+            &state,
+            self.depth,
+            self.max_nodes,
+            starting,
+            move_count,
+            rng,
+            None,
+        )
+    }
+
+    /// Iterative deepening search that ignores the depth and node budgets and keeps going
+    /// deeper until `deadline` expires. Returns the result of the deepest completed search.
+    ///
+    /// # Panics
+    /// Panics if there are no empty tiles on the board
+    pub fn search_until(
+        &mut self,
+        board: &Board,
+        active: Colour,
+        starting: Colour,
+        move_count: u16,
+        rng: &mut (impl Rand + ?Sized),
+        deadline: &dyn Deadline,
+    ) -> SearchResult {
+        let mut state = State::from(board.clone());
+        state.set_to_play(active);
+        let empty = board
+            .iter()
+            .filter(|(_, s)| *s == PieceState::Empty)
+            .count();
+
+        let mut best = None;
+        for depth in 1..=empty.min(u8::MAX as usize) as u8 {
+            let result = self.run(
+                &state,
+                depth,
+                u64::MAX,
+                starting,
+                move_count,
+                rng,
+                Some(deadline),
+            );
+            if self.aborted {
+                break;
+            }
+            let solved = result.score.abs() > WIN - i32::from(depth) - 1;
+            best = Some(result);
+            if solved {
+                break;
+            }
+        }
+        // Always have a move, even if not even the shallowest search finished in time
+        best.unwrap_or_else(|| self.run(&state, 1, u64::MAX, starting, move_count, rng, None))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn run(
+        &mut self,
+        state: &State,
+        depth: u8,
+        max_nodes: u64,
+        starting: Colour,
+        move_count: u16,
+        rng: &mut (impl Rand + ?Sized),
+        deadline: Option<&dyn Deadline>,
+    ) -> SearchResult {
+        self.nodes = 0;
+        self.aborted = false;
+
+        // There is nothing to search once the game is over, but the caller still gets a move
+        if let Some(winner) = state.check_win() {
+            let active = state.active();
+            let ((i, j), _) =
+                PotentialEvaluator::new(state.get_board(), active, starting, &self.params)
+                    .with_bitboard(state.bitboard())
+                    .evaluate()
+                    .get_scored_best_move(move_count, rng);
+            return SearchResult {
+                score: if winner == active { WIN } else { -WIN },
+                pv: vec![Tile::Regular(i, j)],
+                nodes: 0,
+            };
+        }
+
+        let max_nodes = core::mem::replace(&mut self.max_nodes, max_nodes);
+
+        let (score, pv) = self.negamax(
+            state,
+            depth.max(1),
+            -WIN,
+            WIN,
+            0,
+            starting,
+            move_count,
+            rng,
+            deadline,
+        );
+        self.max_nodes = max_nodes;
+        SearchResult {
+            score,
+            pv,
+            nodes: self.nodes,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(
+        &mut self,
+        state: &State,
+        depth: u8,
+        mut alpha: i32,
+        beta: i32,
+        ply: i32,
+        starting: Colour,
+        move_count: u16,
+        rng: &mut (impl Rand + ?Sized),
+        deadline: Option<&dyn Deadline>,
+    ) -> (i32, Vec<Tile>) {
+        self.nodes += 1;
+        if ply > 0 && deadline.is_some_and(Deadline::expired) {
+            self.aborted = true;
+        }
+        if self.aborted {
+            return (0, Vec::new());
+        }
+
+        // Only the player who just moved can have won
+        if state.check_win().is_some() {
+            return (ply - WIN, Vec::new());
+        }
+
+        let active = state.active();
+        let mut evaluator =
+            PotentialEvaluator::new(state.get_board(), active, starting, &self.params)
+                .with_bitboard(state.bitboard());
+        evaluator.evaluate();
+
+        if depth == 0 || self.nodes >= self.max_nodes {
+            let score = evaluator.get_colour_potential(active.opponent())
+                - evaluator.get_colour_potential(active);
+            return (score, Vec::new());
+        }
+
+        let mut moves = evaluator.get_move_scores(move_count, rng);
+        moves.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        let mut best = (-WIN, Vec::new());
+        for ((i, j), _) in moves.into_iter().take(self.width) {
+            let mv = Tile::Regular(i, j);
+            let mut child = state.clone();
+            child
+                .place_piece(mv, PieceState::Colour(active))
+                .expect("valid move");
+            let (score, line) = self.negamax(
+                &child,
+                depth - 1,
+                -beta,
+                -alpha,
+                ply + 1,
+                starting,
+                move_count + 1,
+                rng,
+                deadline,
+            );
+            if self.aborted {
+                break;
+            }
+            let score = -score;
+            if best.1.is_empty() || score > best.0 {
+                let mut pv = Vec::with_capacity(line.len() + 1);
+                pv.push(mv);
+                pv.extend(line);
+                best = (score, pv);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+}
+
+impl Engine for AlphaBeta {
+    fn best_move(
+        &mut self,
+        board: &Board,
+        active: Colour,
+        starting: Colour,
+        move_count: u16,
+        rng: &mut dyn Rand,
+    ) -> Evaluation {
+        let result = self.search(board, active, starting, move_count, rng);
+        Evaluation {
+            mv: result.pv[0],
+            score: Some(result.score as f32),
+        }
+    }
+
+    fn best_move_until(
+        &mut self,
+        board: &Board,
+        active: Colour,
+        starting: Colour,
+        move_count: u16,
+        rng: &mut dyn Rand,
+        deadline: &dyn Deadline,
+    ) -> Evaluation {
+        let result = self.search_until(board, active, starting, move_count, rng, deadline);
+        Evaluation {
+            mv: result.pv[0],
+            score: Some(result.score as f32),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TestRng;
+
+    #[test]
+    fn finds_winning_move() {
+        let mut board = Board::new(4);
+        for r in [0, 1, 3] {
+            board
+                .set_tile(Tile::Regular(r, 1), PieceState::Colour(Colour::Black))
+                .unwrap();
+        }
+        for c in [0, 2, 3] {
+            board
+                .set_tile(Tile::Regular(2, c), PieceState::Colour(Colour::White))
+                .unwrap();
+        }
+        let result =
+            AlphaBeta::default().search(&board, Colour::White, Colour::Black, 6, &mut TestRng(1));
+        assert_eq!(result.pv[0], Tile::Regular(2, 1));
+        assert_eq!(result.score, WIN - 1);
+    }
+
+    struct Expired;
+
+    impl Deadline for Expired {
+        fn expired(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn moves_when_out_of_time() {
+        let board = Board::new(5);
+        let result = AlphaBeta::default().search_until(
+            &board,
+            Colour::Black,
+            Colour::Black,
+            0,
+            &mut TestRng(1),
+            &Expired,
+        );
+        assert_eq!(board.get_tile(result.pv[0]), Some(PieceState::Empty));
+    }
+
+    #[test]
+    fn plays_on_after_a_win() {
+        let mut board = Board::new(3);
+        for r in 0..3 {
+            board
+                .set_tile(Tile::Regular(r, 0), PieceState::Colour(Colour::Black))
+                .unwrap();
+        }
+        let result =
+            AlphaBeta::default().search(&board, Colour::White, Colour::Black, 3, &mut TestRng(1));
+        assert_eq!(result.score, -WIN);
+        assert_eq!(result.pv.len(), 1);
+        assert_eq!(board.get_tile(result.pv[0]), Some(PieceState::Empty));
+    }
+}
@@ -0,0 +1,97 @@
+use alloc::boxed::Box;
+use pincerhex_core::{Engine, EvalParams, PotentialEngine};
+
+use crate::{
+    alphabeta::{self, AlphaBeta},
+    mcts::{self, Mcts, Playout},
+};
+
+/// Move generator used by the bot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineKind {
+    /// Single pass of the potential evaluator
+    Potential,
+    /// Monte Carlo Tree Search
+    Mcts,
+    /// Monte Carlo Tree Search with playouts guided by the potential evaluator
+    MctsGuided,
+    /// Alpha-beta search over the potential evaluation
+    AlphaBeta,
+}
+
+#[derive(Debug)]
+pub struct InvalidEngine;
+
+impl core::fmt::Display for InvalidEngine {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid engine")
+    }
+}
+
+impl TryFrom<&str> for EngineKind {
+    type Error = InvalidEngine;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "potential" => Ok(Self::Potential),
+            "mcts" => Ok(Self::Mcts),
+            "mcts-guided" => Ok(Self::MctsGuided),
+            "alphabeta" => Ok(Self::AlphaBeta),
+            _ => Err(InvalidEngine),
+        }
+    }
+}
+
+impl core::fmt::Display for EngineKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Potential => write!(f, "potential"),
+            Self::Mcts => write!(f, "mcts"),
+            Self::MctsGuided => write!(f, "mcts-guided"),
+            Self::AlphaBeta => write!(f, "alphabeta"),
+        }
+    }
+}
+
+impl EngineKind {
+    pub const ALL: [Self; 4] = [
+        Self::Potential,
+        Self::Mcts,
+        Self::MctsGuided,
+        Self::AlphaBeta,
+    ];
+
+    /// Creates the engine with its default search budget
+    #[must_use]
+    pub fn engine(self, params: EvalParams) -> Box<dyn Engine> {
+        match self {
+            Self::Potential => Box::new(PotentialEngine::new(params)),
+            Self::Mcts => Box::new(Mcts::new(mcts::DEFAULT_ITERATIONS, Playout::Random, params)),
+            Self::MctsGuided => {
+                Box::new(Mcts::new(mcts::DEFAULT_ITERATIONS, Playout::Guided, params))
+            }
+            Self::AlphaBeta => Box::new(AlphaBeta::new(
+                alphabeta::DEFAULT_DEPTH,
+                alphabeta::DEFAULT_WIDTH,
+                alphabeta::DEFAULT_MAX_NODES,
+                params,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn names_round_trip() {
+        for kind in EngineKind::ALL {
+            assert_eq!(
+                EngineKind::try_from(kind.to_string().as_str()).ok(),
+                Some(kind)
+            );
+        }
+    }
+}
@@ -10,18 +10,40 @@
     clippy::cast_possible_truncation
 )]
 
+#[macro_use]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
+#[cfg(feature = "std")]
 mod ai;
+pub mod alphabeta;
+mod engine;
+pub mod mcts;
+pub mod selfplay;
 
+#[cfg(feature = "std")]
 pub use ai::{BotError, HexBot};
-pub use pincerhex_core::{Colour, Move, PieceState};
-pub use pincerhex_state::Winner;
-use rand::{rngs::ThreadRng, Rng};
+pub use engine::{EngineKind, InvalidEngine};
+pub use pincerhex_core::{Colour, Engine, EvalParams, Evaluation, Move, PieceState, Rand};
+pub use pincerhex_state::{SwapKind, Winner};
+#[cfg(feature = "std")]
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
-struct StdRng(ThreadRng);
+/// Reproducible [`Rand`](pincerhex_core::Rand) backed by the standard RNG
+#[cfg(feature = "std")]
+pub struct SeededRng(StdRng);
 
-impl pincerhex_core::Rand for StdRng {
+#[cfg(feature = "std")]
+impl SeededRng {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
+
+#[cfg(feature = "std")]
+impl pincerhex_core::Rand for SeededRng {
     fn in_range(&mut self, a: i8, b: i8) -> i8 {
         self.0.gen_range(a..b)
     }
@@ -30,3 +52,18 @@ impl pincerhex_core::Rand for StdRng {
         self.0.gen::<f32>()
     }
 }
+
+#[cfg(test)]
+struct TestRng(u32);
+
+#[cfg(test)]
+impl pincerhex_core::Rand for TestRng {
+    fn in_range(&mut self, a: i8, _b: i8) -> i8 {
+        a
+    }
+
+    fn next(&mut self) -> f32 {
+        self.0 = self.0.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        (self.0 >> 8) as f32 / (1 << 24) as f32
+    }
+}
@@ -0,0 +1,372 @@
+use alloc::vec::Vec;
+#[allow(unused_imports)]
+use micromath::F32Ext;
+use pincerhex_core::{
+    Board, Colour, Deadline, Engine, EvalParams, Evaluation, PieceState, PotentialEvaluator, Rand,
+    Tile,
+};
+use pincerhex_state::State;
+
+pub const DEFAULT_ITERATIONS: u32 = 2000;
+const DEFAULT_EXPLORATION: f32 = 0.7;
+
+/// How moves are picked once a playout leaves the search tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Playout {
+    /// Uniformly random moves.
+    Random,
+    /// Moves biased towards tiles the potential evaluator likes at the root.
+    Guided,
+}
+
+/// Monte Carlo Tree Search using UCT for selection.
+pub struct Mcts {
+    iterations: u32,
+    exploration: f32,
+    playout: Playout,
+    /// Evaluator parameters for guided playouts
+    params: EvalParams,
+}
+
+struct Node {
+    mv: Option<Tile>,
+    /// Colour that played `mv`
+    colour: Colour,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<Tile>,
+    visits: u32,
+    wins: f32,
+}
+
+impl Node {
+    fn uct(&self, parent_visits: f32, exploration: f32) -> f32 {
+        let visits = self.visits as f32;
+        self.wins / visits + exploration * (parent_visits.ln() / visits).sqrt()
+    }
+}
+
+impl Default for Mcts {
+    fn default() -> Self {
+        Self::new(DEFAULT_ITERATIONS, Playout::Random, EvalParams::DEFAULT)
+    }
+}
+
+impl Mcts {
+    #[must_use]
+    pub const fn new(iterations: u32, playout: Playout, params: EvalParams) -> Self {
+        Self {
+            iterations,
+            exploration: DEFAULT_EXPLORATION,
+            playout,
+            params,
+        }
+    }
+
+    /// Searches for the best move for `active`. Returns the move along with its estimated win
+    /// rate.
+    ///
+    /// # Panics
+    /// Panics if there are no empty tiles on the board
+    pub fn search(
+        &self,
+        board: &Board,
+        active: Colour,
+        starting: Colour,
+        move_count: u16,
+        rng: &mut (impl Rand + ?Sized),
+    ) -> (Tile, f32) {
+        self.run(board, active, starting, move_count, rng, None)
+    }
+
+    /// Same as [`Self::search`] but runs as many iterations as fit before `deadline`.
+    ///
+    /// # Panics
+    /// Panics if there are no empty tiles on the board
+    pub fn search_until(
+        &self,
+        board: &Board,
+        active: Colour,
+        starting: Colour,
+        move_count: u16,
+        rng: &mut (impl Rand + ?Sized),
+        deadline: &dyn Deadline,
+    ) -> (Tile, f32) {
+        self.run(board, active, starting, move_count, rng, Some(deadline))
+    }
+
+    fn run(
+        &self,
+        board: &Board,
+        active: Colour,
+        starting: Colour,
+        move_count: u16,
+        rng: &mut (impl Rand + ?Sized),
+        deadline: Option<&dyn Deadline>,
+    ) -> (Tile, f32) {
+        let mut root_state = State::from(board.clone());
+        root_state.set_to_play(active);
+
+        let priors = match self.playout {
+            Playout::Random => None,
+            Playout::Guided => Some(self.priors(&root_state, active, starting, move_count, rng)),
+        };
+
+        let mut tree = vec![Node {
+            mv: None,
+            colour: active.opponent(),
+            parent: None,
+            children: Vec::new(),
+            untried: Self::empty_tiles(board, priors.as_deref()),
+            visits: 0,
+            wins: 0.,
+        }];
+        assert!(!tree[0].untried.is_empty(), "no moves left to search");
+
+        for iteration in 0.. {
+            let done = deadline.map_or(iteration >= self.iterations, |deadline| {
+                iteration > 0 && deadline.expired()
+            });
+            if done {
+                break;
+            }
+            let mut state = root_state.clone();
+            let mut node = 0;
+
+            // Selection
+            while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+                node = self.select(&tree, node);
+                Self::play(&mut state, tree[node].mv, tree[node].colour);
+            }
+
+            // Expansion
+            let mut winner = state.check_win();
+            if winner.is_none() {
+                if let Some(mv) = Self::pick(&mut tree[node].untried, priors.is_some(), rng) {
+                    let colour = state.active();
+                    Self::play(&mut state, Some(mv), colour);
+                    let untried = Self::empty_tiles(state.get_board(), priors.as_deref());
+                    tree.push(Node {
+                        mv: Some(mv),
+                        colour,
+                        parent: Some(node),
+                        children: Vec::new(),
+                        untried,
+                        visits: 0,
+                        wins: 0.,
+                    });
+                    let child = tree.len() - 1;
+                    tree[node].children.push(child);
+                    node = child;
+                    winner = state.check_win();
+                }
+            }
+
+            // Simulation
+            let winner = winner.unwrap_or_else(|| Self::simulate(&state, priors.as_deref(), rng));
+
+            // Backpropagation
+            let mut current = Some(node);
+            while let Some(idx) = current {
+                let n = &mut tree[idx];
+                n.visits += 1;
+                if n.colour == winner {
+                    n.wins += 1.;
+                }
+                current = n.parent;
+            }
+        }
+
+        tree[0]
+            .children
+            .iter()
+            .map(|&c| &tree[c])
+            .max_by_key(|n| n.visits)
+            .map_or_else(
+                || (tree[0].untried[0], 0.),
+                |n| (n.mv.expect("child has a move"), n.wins / n.visits as f32),
+            )
+    }
+
+    fn select(&self, tree: &[Node], node: usize) -> usize {
+        let parent_visits = tree[node].visits as f32;
+        let mut best = tree[node].children[0];
+        let mut best_score = f32::MIN;
+        for &child in &tree[node].children {
+            let score = tree[child].uct(parent_visits, self.exploration);
+            if score > best_score {
+                best_score = score;
+                best = child;
+            }
+        }
+        best
+    }
+
+    fn play(state: &mut State, mv: Option<Tile>, colour: Colour) {
+        if let Some(mv) = mv {
+            state
+                .place_piece(mv, PieceState::Colour(colour))
+                .expect("valid move");
+        }
+    }
+
+    /// Fills the rest of the board and returns the winner. A full Hex board always has exactly
+    /// one winner, so the groups are only needed once and the bitboard is filled instead.
+    fn simulate(state: &State, priors: Option<&[f32]>, rng: &mut (impl Rand + ?Sized)) -> Colour {
+        let mut board = state.bitboard().clone();
+        let mut empty = Self::empty_tiles(state.get_board(), None);
+        let mut colour = state.active();
+        while let Some(mv) = Self::pick_playout(&mut empty, priors, board.size(), rng) {
+            board
+                .set_tile(mv, PieceState::Colour(colour))
+                .expect("valid move");
+            colour = colour.opponent();
+        }
+        board.winner().expect("full board has a winner")
+    }
+
+    /// Pick a random untried move, or the best one if the moves are ordered by priors.
+    fn pick(
+        untried: &mut Vec<Tile>,
+        ordered: bool,
+        rng: &mut (impl Rand + ?Sized),
+    ) -> Option<Tile> {
+        if untried.is_empty() {
+            None
+        } else if ordered {
+            untried.pop()
+        } else {
+            Some(untried.swap_remove(random_index(rng, untried.len())))
+        }
+    }
+
+    fn pick_playout(
+        empty: &mut Vec<Tile>,
+        priors: Option<&[f32]>,
+        size: i8,
+        rng: &mut (impl Rand + ?Sized),
+    ) -> Option<Tile> {
+        if empty.is_empty() {
+            return None;
+        }
+        let mut idx = random_index(rng, empty.len());
+        if let Some(priors) = priors {
+            // Tournament selection between two random candidates
+            let other = random_index(rng, empty.len());
+            let score = |i: usize| {
+                empty[i]
+                    .to_index(size)
+                    .map_or(f32::MAX, |tile| priors[tile])
+            };
+            if score(other) < score(idx) {
+                idx = other;
+            }
+        }
+        Some(empty.swap_remove(idx))
+    }
+
+    /// Empty tiles on the board. When priors are given the best tile is last.
+    fn empty_tiles(board: &Board, priors: Option<&[f32]>) -> Vec<Tile> {
+        let mut empty = board
+            .iter()
+            .filter(|(_, s)| *s == PieceState::Empty)
+            .map(|(t, _)| t)
+            .collect::<Vec<Tile>>();
+        if let Some(priors) = priors {
+            let score = |t: &Tile| t.to_index(board.size).map_or(f32::MAX, |i| priors[i]);
+            empty.sort_by(|a, b| score(b).total_cmp(&score(a)));
+        }
+        empty
+    }
+
+    /// Potential evaluator scores for every tile, indexed by tile index. Lower is better.
+    fn priors(
+        &self,
+        state: &State,
+        active: Colour,
+        starting: Colour,
+        move_count: u16,
+        rng: &mut (impl Rand + ?Sized),
+    ) -> Vec<f32> {
+        let board = state.get_board();
+        let mut priors = vec![f32::MAX; (board.size as usize).pow(2)];
+        for ((i, j), score) in PotentialEvaluator::new(board, active, starting, &self.params)
+            .with_bitboard(state.bitboard())
+            .evaluate()
+            .get_move_scores(move_count, rng)
+        {
+            if let Some(idx) = Tile::Regular(i, j).to_index(board.size) {
+                priors[idx] = score;
+            }
+        }
+        priors
+    }
+}
+
+impl Engine for Mcts {
+    fn best_move(
+        &mut self,
+        board: &Board,
+        active: Colour,
+        starting: Colour,
+        move_count: u16,
+        rng: &mut dyn Rand,
+    ) -> Evaluation {
+        let (mv, win_rate) = self.search(board, active, starting, move_count, rng);
+        Evaluation {
+            mv,
+            score: Some(win_rate),
+        }
+    }
+
+    fn best_move_until(
+        &mut self,
+        board: &Board,
+        active: Colour,
+        starting: Colour,
+        move_count: u16,
+        rng: &mut dyn Rand,
+        deadline: &dyn Deadline,
+    ) -> Evaluation {
+        let (mv, win_rate) = self.search_until(board, active, starting, move_count, rng, deadline);
+        Evaluation {
+            mv,
+            score: Some(win_rate),
+        }
+    }
+}
+
+fn random_index(rng: &mut (impl Rand + ?Sized), len: usize) -> usize {
+    ((rng.next() * len as f32) as usize).min(len - 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TestRng;
+
+    #[test]
+    fn finds_winning_move() {
+        let mut board = Board::new(4);
+        for r in [0, 1, 3] {
+            board
+                .set_tile(Tile::Regular(r, 1), PieceState::Colour(Colour::Black))
+                .unwrap();
+        }
+        for c in [0, 2, 3] {
+            board
+                .set_tile(Tile::Regular(2, c), PieceState::Colour(Colour::White))
+                .unwrap();
+        }
+        for playout in [Playout::Random, Playout::Guided] {
+            let (mv, _) = Mcts::new(500, playout, EvalParams::DEFAULT).search(
+                &board,
+                Colour::Black,
+                Colour::Black,
+                6,
+                &mut TestRng(1),
+            );
+            assert_eq!(mv, Tile::Regular(2, 1));
+        }
+    }
+}
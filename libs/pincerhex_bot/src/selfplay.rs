@@ -0,0 +1,234 @@
+use alloc::vec::Vec;
+#[allow(unused_imports)]
+use micromath::F32Ext;
+use pincerhex_core::{first_move, Colour, Engine, Move, PieceState, Rand, Tile};
+use pincerhex_state::State;
+
+/// z-score of a 95% confidence interval
+const Z_95: f32 = 1.96;
+
+/// Settings shared by every game of a match.
+#[derive(Debug, Clone, Copy)]
+pub struct GameOptions {
+    pub size: i8,
+    pub swap_rule: bool,
+    /// Random moves played after the opening move, before the engines take over
+    pub random_moves: u16,
+}
+
+/// A finished game between two engines.
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    /// Moves in the order they were played, starting with black's opening move
+    pub moves: Vec<Move>,
+    /// Whether the second player swapped after the opening move
+    pub swapped: bool,
+    /// Colour that connected its edges
+    pub winner: Colour,
+    /// Index of the player that won, 0 for the first player of [`play_game`] and 1 for the
+    /// second
+    pub winning_player: usize,
+}
+
+/// Plays a game between two engines.
+///
+/// The `first` player opens as black with a random [`first_move`]. With the swap rule the
+/// `second` player may take over the opening stone, in which case the players switch colours.
+/// Games are adjudicated with [`State::check_win`].
+///
+/// # Panics
+/// Panics if an engine returns an illegal move
+pub fn play_game(
+    first: &mut dyn Engine,
+    second: &mut dyn Engine,
+    options: GameOptions,
+    rng: &mut dyn Rand,
+) -> GameRecord {
+    let size = options.size;
+    let mut state = State::with_swap_rule(size, options.swap_rule);
+    let (i, j) = first_move(size, rng);
+    let opening = Tile::Regular(i, j);
+    state
+        .place_piece(opening, PieceState::Colour(Colour::Black))
+        .expect("valid opening");
+    let mut moves = vec![Move::Move(opening)];
+
+    let swapped = state.should_swap(rng);
+    if swapped {
+        state.swap().expect("swap after the opening");
+        moves.push(Move::Swap);
+    }
+    // Index of the player playing black
+    let black = usize::from(swapped);
+
+    let mut move_count = 1;
+    for _ in 0..options.random_moves {
+        if state.check_win().is_some() {
+            break;
+        }
+        let empty = state
+            .get_board()
+            .iter()
+            .filter(|(_, s)| *s == PieceState::Empty)
+            .map(|(t, _)| t)
+            .collect::<Vec<_>>();
+        let mv = empty[((rng.next() * empty.len() as f32) as usize).min(empty.len() - 1)];
+        state
+            .place_piece(mv, PieceState::Colour(state.active()))
+            .expect("valid move");
+        moves.push(Move::Move(mv));
+        move_count += 1;
+    }
+
+    let winner = loop {
+        if let Some(winner) = state.check_win() {
+            break winner;
+        }
+        let active = state.active();
+        let player = if active == Colour::Black {
+            black
+        } else {
+            1 - black
+        };
+        let engine: &mut dyn Engine = if player == 0 {
+            &mut *first
+        } else {
+            &mut *second
+        };
+        let mv = engine
+            .best_move(state.get_board(), active, Colour::Black, move_count, rng)
+            .mv;
+        state
+            .try_place_piece(mv, PieceState::Colour(active))
+            .expect("engine played a legal move");
+        moves.push(Move::Move(mv));
+        move_count += 1;
+    };
+
+    GameRecord {
+        moves,
+        swapped,
+        winner,
+        winning_player: if winner == Colour::Black {
+            black
+        } else {
+            1 - black
+        },
+    }
+}
+
+/// Results of a match from the point of view of one of the players.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MatchScore {
+    pub wins: u32,
+    pub losses: u32,
+}
+
+impl MatchScore {
+    #[must_use]
+    pub const fn games(self) -> u32 {
+        self.wins + self.losses
+    }
+
+    /// Fraction of games won
+    #[must_use]
+    pub fn win_rate(self) -> f32 {
+        if self.games() == 0 {
+            0.5
+        } else {
+            self.wins as f32 / self.games() as f32
+        }
+    }
+
+    /// Wins minus losses, scaled to `-1..=1`
+    #[must_use]
+    pub fn result(self) -> f32 {
+        2. * self.win_rate() - 1.
+    }
+
+    /// Elo difference to the opponent. Infinite if every game was won or lost.
+    #[must_use]
+    pub fn elo(self) -> f32 {
+        elo(self.win_rate())
+    }
+
+    /// Bounds of the 95% confidence interval of [`Self::elo`]
+    #[must_use]
+    pub fn elo_interval(self) -> (f32, f32) {
+        let p = self.win_rate();
+        let margin = Z_95 * (p * (1. - p) / self.games().max(1) as f32).sqrt();
+        (elo((p - margin).max(0.)), elo((p + margin).min(1.)))
+    }
+}
+
+impl core::fmt::Display for MatchScore {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "+{} -{} ({:.1}%)",
+            self.wins,
+            self.losses,
+            100. * self.win_rate()
+        )
+    }
+}
+
+fn elo(win_rate: f32) -> f32 {
+    if win_rate <= 0. {
+        f32::NEG_INFINITY
+    } else if win_rate >= 1. {
+        f32::INFINITY
+    } else {
+        400. * (win_rate / (1. - win_rate)).log10()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TestRng;
+    use pincerhex_core::{EvalParams, PotentialEngine};
+
+    #[test]
+    fn game_ends_with_a_winner() {
+        let mut first = PotentialEngine::new(EvalParams::DEFAULT);
+        let mut second = PotentialEngine::new(EvalParams::DEFAULT);
+        let options = GameOptions {
+            size: 5,
+            swap_rule: true,
+            random_moves: 2,
+        };
+        let record = play_game(&mut first, &mut second, options, &mut TestRng(3));
+        assert!(record.moves.len() >= 9);
+
+        let mut state = State::new(5);
+        let mut colour = Colour::Black;
+        for mv in &record.moves {
+            if let Move::Move(tile) = mv {
+                state
+                    .place_piece(*tile, PieceState::Colour(colour))
+                    .unwrap();
+                colour = colour.opponent();
+            }
+        }
+        assert_eq!(state.check_win(), Some(record.winner));
+    }
+
+    #[test]
+    fn elo_difference() {
+        let even = MatchScore {
+            wins: 10,
+            losses: 10,
+        };
+        assert!(even.elo().abs() < 1.);
+        let (low, high) = even.elo_interval();
+        assert!(low < 0. && high > 0.);
+
+        let better = MatchScore {
+            wins: 76,
+            losses: 24,
+        };
+        assert!((better.elo() - 200.).abs() < 5.);
+        assert!(MatchScore { wins: 3, losses: 0 }.elo().is_infinite());
+    }
+}
@@ -0,0 +1,508 @@
+//! Board stored as one bit set per colour. Neighbours are found by shifting whole bit sets, so
+//! flood-filling a colour's groups and detecting the winner take a few word operations per
+//! step instead of a lookup per cell.
+//!
+//! The bit sets are only as wide as the board needs: two words for boards up to 11, so a board
+//! fits in a pair of `u128`s, and up to sixteen words for the largest boards.
+
+use alloc::{sync::Arc, vec::Vec};
+use core::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
+
+use crate::{
+    board::{Board, Error},
+    tile::{Colour, PieceState, Tile, MAX_SIZE},
+};
+
+/// Set of cells, indexed like [`Tile::to_index`], in `W` words
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Bits<const W: usize>([u64; W]);
+
+impl<const W: usize> Default for Bits<W> {
+    fn default() -> Self {
+        Self([0; W])
+    }
+}
+
+impl<const W: usize> Bits<W> {
+    #[must_use]
+    pub fn single(idx: usize) -> Self {
+        let mut bits = Self::default();
+        bits.insert(idx);
+        bits
+    }
+
+    #[must_use]
+    pub fn contains(&self, idx: usize) -> bool {
+        self.0
+            .get(idx / 64)
+            .is_some_and(|w| w & (1 << (idx % 64)) != 0)
+    }
+
+    pub fn insert(&mut self, idx: usize) {
+        if let Some(w) = self.0.get_mut(idx / 64) {
+            *w |= 1 << (idx % 64);
+        }
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        if let Some(w) = self.0.get_mut(idx / 64) {
+            *w &= !(1 << (idx % 64));
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&w| w == 0)
+    }
+
+    /// Indices of the cells in the set, in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        indices(&self.0)
+    }
+}
+
+/// Indices of the set bits of `words`, in increasing order
+fn indices(words: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    words.iter().enumerate().flat_map(|(i, &word)| {
+        let mut word = word;
+        core::iter::from_fn(move || {
+            (word != 0).then(|| {
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                i * 64 + bit
+            })
+        })
+    })
+}
+
+impl<const W: usize> BitAnd for Bits<W> {
+    type Output = Self;
+
+    fn bitand(mut self, rhs: Self) -> Self {
+        self.0.iter_mut().zip(rhs.0).for_each(|(a, b)| *a &= b);
+        self
+    }
+}
+
+impl<const W: usize> BitOr for Bits<W> {
+    type Output = Self;
+
+    fn bitor(mut self, rhs: Self) -> Self {
+        self.0.iter_mut().zip(rhs.0).for_each(|(a, b)| *a |= b);
+        self
+    }
+}
+
+impl<const W: usize> BitXor for Bits<W> {
+    type Output = Self;
+
+    fn bitxor(mut self, rhs: Self) -> Self {
+        self.0.iter_mut().zip(rhs.0).for_each(|(a, b)| *a ^= b);
+        self
+    }
+}
+
+impl<const W: usize> Not for Bits<W> {
+    type Output = Self;
+
+    fn not(mut self) -> Self {
+        self.0.iter_mut().for_each(|a| *a = !*a);
+        self
+    }
+}
+
+/// Moves every cell `n` indices up. `n` must be less than 64.
+impl<const W: usize> Shl<usize> for Bits<W> {
+    type Output = Self;
+
+    fn shl(self, n: usize) -> Self {
+        if n == 0 {
+            return self;
+        }
+        let mut out = self.0.map(|w| w << n);
+        for (i, word) in out.iter_mut().enumerate().skip(1) {
+            *word |= self.0[i - 1] >> (64 - n);
+        }
+        Self(out)
+    }
+}
+
+/// Moves every cell `n` indices down. `n` must be less than 64.
+impl<const W: usize> Shr<usize> for Bits<W> {
+    type Output = Self;
+
+    fn shr(self, n: usize) -> Self {
+        if n == 0 {
+            return self;
+        }
+        let mut out = self.0.map(|w| w >> n);
+        for (word, next) in out.iter_mut().zip(&self.0[1..]) {
+            *word |= next << (64 - n);
+        }
+        Self(out)
+    }
+}
+
+/// Masks that only depend on the board size
+#[derive(Debug)]
+struct Masks<const W: usize> {
+    board: Bits<W>,
+    not_first_col: Bits<W>,
+    not_last_col: Bits<W>,
+    /// First and last edge of each colour, by `Colour::group_idx`
+    edges: [[Bits<W>; 2]; 2],
+    neighbours: Vec<Bits<W>>,
+}
+
+impl<const W: usize> Masks<W> {
+    fn new(size: i8) -> Self {
+        let s = size as usize;
+        let mut masks = Self {
+            board: Bits::default(),
+            not_first_col: Bits::default(),
+            not_last_col: Bits::default(),
+            edges: [[Bits::default(); 2]; 2],
+            neighbours: Vec::with_capacity(s * s),
+        };
+        for idx in 0..s * s {
+            let (row, col) = (idx / s, idx % s);
+            masks.board.insert(idx);
+            if col != 0 {
+                masks.not_first_col.insert(idx);
+            }
+            if col != s - 1 {
+                masks.not_last_col.insert(idx);
+            }
+            for (edge, colour) in [(row, Colour::Black), (col, Colour::White)] {
+                if edge == 0 {
+                    masks.edges[colour.group_idx()][0].insert(idx);
+                }
+                if edge == s - 1 {
+                    masks.edges[colour.group_idx()][1].insert(idx);
+                }
+            }
+        }
+        for idx in 0..s * s {
+            let cell = Bits::single(idx);
+            let neighbours = masks.dilate(cell, s) ^ cell;
+            masks.neighbours.push(neighbours);
+        }
+        masks
+    }
+
+    /// `bits` and all of their neighbours
+    fn dilate(&self, bits: Bits<W>, size: usize) -> Bits<W> {
+        let up = size.saturating_sub(1);
+        (bits
+            | ((bits << 1) & self.not_first_col)
+            | ((bits >> 1) & self.not_last_col)
+            | (bits << size)
+            | (bits >> size)
+            | ((bits << up) & self.not_last_col)
+            | ((bits >> up) & self.not_first_col))
+            & self.board
+    }
+}
+
+/// Stones of a board whose cells fit in `W` words
+#[derive(Debug, Clone)]
+struct Grid<const W: usize> {
+    size: i8,
+    stones: [Bits<W>; 2],
+    /// Built once by `new` and shared by every copy
+    masks: Arc<Masks<W>>,
+}
+
+impl<const W: usize> Grid<W> {
+    fn new(size: i8) -> Self {
+        Self {
+            size,
+            stones: [Bits::default(); 2],
+            masks: Arc::new(Masks::new(size)),
+        }
+    }
+
+    fn get(&self, idx: usize) -> PieceState {
+        if self.stones[Colour::Black.group_idx()].contains(idx) {
+            PieceState::Colour(Colour::Black)
+        } else if self.stones[Colour::White.group_idx()].contains(idx) {
+            PieceState::Colour(Colour::White)
+        } else {
+            PieceState::Empty
+        }
+    }
+
+    fn set(&mut self, idx: usize, s: PieceState) {
+        for stones in &mut self.stones {
+            stones.remove(idx);
+        }
+        if let PieceState::Colour(c) = s {
+            self.stones[c.group_idx()].insert(idx);
+        }
+    }
+
+    fn empty(&self) -> Bits<W> {
+        self.masks.board & !(self.stones[0] | self.stones[1])
+    }
+
+    /// Stones of `colour` connected to its first edge
+    fn flood_fill(&self, colour: Colour) -> Bits<W> {
+        let stones = self.stones[colour.group_idx()];
+        let mut reached = stones & self.masks.edges[colour.group_idx()][0];
+        loop {
+            let next = self.masks.dilate(reached, self.size as usize) & stones;
+            if next == reached {
+                return reached;
+            }
+            reached = next;
+        }
+    }
+
+    fn is_connected(&self, colour: Colour) -> bool {
+        !(self.flood_fill(colour) & self.masks.edges[colour.group_idx()][1]).is_empty()
+    }
+
+    fn shortest_path(&self, colour: Colour) -> Option<Vec<usize>> {
+        let stones = self.stones[colour.group_idx()];
+        let [first, last] = self.masks.edges[colour.group_idx()];
+        // Stones by their distance from the first edge, until one touches the last edge
+        let mut layers = vec![stones & first];
+        let mut reached = layers[0];
+        loop {
+            let layer = layers[layers.len() - 1];
+            if !(layer & last).is_empty() {
+                break;
+            }
+            if layer.is_empty() {
+                return None;
+            }
+            let next = self.masks.dilate(layer, self.size as usize) & stones & !reached;
+            reached = reached | next;
+            layers.push(next);
+        }
+
+        // Walk back through a neighbour in every closer layer
+        let mut idx = (layers.pop()? & last).iter().next()?;
+        let mut path = vec![idx];
+        while let Some(layer) = layers.pop() {
+            idx = (layer & self.masks.neighbours[idx]).iter().next()?;
+            path.push(idx);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// [`Grid`]s of the widths boards are stored in
+#[derive(Debug, Clone)]
+enum Storage {
+    /// Up to 11 by 11
+    Small(Grid<2>),
+    /// Up to 16 by 16
+    Medium(Grid<4>),
+    /// Up to 22 by 22
+    Large(Grid<8>),
+    /// Up to 32 by 32
+    Huge(Grid<16>),
+}
+
+/// Runs `$body` on the grid of `$storage`, whatever its width
+macro_rules! with_grid {
+    ($storage: expr, $grid: ident => $body: expr) => {
+        match $storage {
+            Storage::Small($grid) => $body,
+            Storage::Medium($grid) => $body,
+            Storage::Large($grid) => $body,
+            Storage::Huge($grid) => $body,
+        }
+    };
+}
+
+#[derive(Debug, Clone)]
+pub struct Bitboard(Storage);
+
+impl Bitboard {
+    /// Masks are built here and shared by every clone, so a search only builds them once for
+    /// its root position.
+    ///
+    /// # Panics
+    /// Panics if `size` is larger than `MAX_SIZE`
+    #[must_use]
+    pub fn new(size: i8) -> Self {
+        assert!(size <= MAX_SIZE, "board size {size} is too large");
+        let cells = (size.max(0) as usize).pow(2);
+        Self(match cells.div_ceil(64) {
+            0..=2 => Storage::Small(Grid::new(size)),
+            3..=4 => Storage::Medium(Grid::new(size)),
+            5..=8 => Storage::Large(Grid::new(size)),
+            _ => Storage::Huge(Grid::new(size)),
+        })
+    }
+
+    #[must_use]
+    pub const fn size(&self) -> i8 {
+        with_grid!(&self.0, grid => grid.size)
+    }
+
+    #[must_use]
+    pub fn get_tile(&self, tile: Tile) -> Option<PieceState> {
+        let idx = tile.to_index(self.size())?;
+        Some(with_grid!(&self.0, grid => grid.get(idx)))
+    }
+
+    /// # Errors
+    /// Will return an `Err` if the given tile is not in range of the board
+    pub fn set_tile(&mut self, tile: Tile, s: PieceState) -> Result<(), Error> {
+        let idx = tile.to_index(self.size()).ok_or(Error::NotInRange)?;
+        with_grid!(&mut self.0, grid => grid.set(idx, s));
+        Ok(())
+    }
+
+    /// Whether there are no empty tiles left
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        with_grid!(&self.0, grid => grid.empty().is_empty())
+    }
+
+    /// Indices of the stones of `colour`, in increasing order
+    pub fn stones(&self, colour: Colour) -> impl Iterator<Item = usize> + '_ {
+        indices(with_grid!(&self.0, grid => &grid.stones[colour.group_idx()].0[..]))
+    }
+
+    /// Indices of the cells next to `tile`, none for tiles not on the board
+    pub fn neighbours(&self, tile: Tile) -> impl Iterator<Item = usize> + '_ {
+        let words = tile.to_index(self.size()).map_or(
+            &[][..],
+            |idx| with_grid!(&self.0, grid => &grid.masks.neighbours[idx].0[..]),
+        );
+        indices(words)
+    }
+
+    /// Whether `colour` connects its edges
+    #[must_use]
+    pub fn is_connected(&self, colour: Colour) -> bool {
+        with_grid!(&self.0, grid => grid.is_connected(colour))
+    }
+
+    /// Checks White first, in the same order as `State::check_win`
+    #[must_use]
+    pub fn winner(&self) -> Option<Colour> {
+        [Colour::White, Colour::Black]
+            .into_iter()
+            .find(|&c| self.is_connected(c))
+    }
+
+    /// Shortest chain of `colour` stones connecting its edges, starting from the first edge
+    #[must_use]
+    pub fn shortest_path(&self, colour: Colour) -> Option<Vec<Tile>> {
+        let path = with_grid!(&self.0, grid => grid.shortest_path(colour))?;
+        Some(
+            path.into_iter()
+                .map(|idx| self.index_to_tile(idx))
+                .collect(),
+        )
+    }
+
+    /// [`Self::winner`] and their shortest winning chain
+    #[must_use]
+    pub fn winning_path(&self) -> Option<(Colour, Vec<Tile>)> {
+        let winner = self.winner()?;
+        Some((winner, self.shortest_path(winner)?))
+    }
+
+    const fn index_to_tile(&self, idx: usize) -> Tile {
+        let size = self.size() as usize;
+        Tile::Regular((idx / size) as i8, (idx % size) as i8)
+    }
+}
+
+impl From<&Board> for Bitboard {
+    fn from(board: &Board) -> Self {
+        let mut bits = Self::new(board.size);
+        for (tile, s) in board.iter() {
+            if let (Some(idx), PieceState::Colour(_)) = (tile.to_index(board.size), s) {
+                with_grid!(&mut bits.0, grid => grid.set(idx, s));
+            }
+        }
+        bits
+    }
+}
+
+impl From<&Bitboard> for Board {
+    fn from(bits: &Bitboard) -> Self {
+        let mut board = Self::new(bits.size());
+        for colour in [Colour::Black, Colour::White] {
+            for idx in bits.stones(colour) {
+                board
+                    .set_tile(board.index_to_tile(idx), PieceState::Colour(colour))
+                    .expect("stones are on the board");
+            }
+        }
+        board
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn neighbours_match_board() {
+        for size in [1, 5, 11, 12, 17, 32] {
+            let board = Board::new(size);
+            let bits = Bitboard::new(size);
+            for (tile, _) in board.iter() {
+                let mut expected = board
+                    .neighbours(tile)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|(t, _)| t.to_index(size))
+                    .collect::<Vec<_>>();
+                expected.sort_unstable();
+                assert_eq!(bits.neighbours(tile).collect::<Vec<_>>(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn storage_fits_the_board() {
+        assert!(matches!(Bitboard::new(11).0, Storage::Small(_)));
+        assert!(matches!(Bitboard::new(12).0, Storage::Medium(_)));
+        assert!(matches!(Bitboard::new(MAX_SIZE).0, Storage::Huge(_)));
+        let bits = Bitboard::new(5);
+        let (Storage::Small(a), Storage::Small(b)) = (&bits.0, &bits.clone().0) else {
+            unreachable!()
+        };
+        assert!(Arc::ptr_eq(&a.masks, &b.masks));
+    }
+
+    #[test]
+    fn detects_the_winner() {
+        let board = Board::try_from("W..B|WWB.|.WB.|BWW.|").unwrap();
+        let mut bits = Bitboard::from(&board);
+        assert_eq!(bits.winner(), None);
+        assert_eq!(Board::from(&bits).get_compressed(), board.get_compressed());
+        bits.set_tile(Tile::Regular(3, 3), PieceState::Colour(Colour::White))
+            .unwrap();
+        assert_eq!(bits.winner(), Some(Colour::White));
+        let Storage::Small(grid) = &bits.0 else {
+            unreachable!()
+        };
+        assert_eq!(grid.flood_fill(Colour::White).iter().count(), 7);
+        assert_eq!(
+            bits.winning_path(),
+            Some((
+                Colour::White,
+                vec![
+                    Tile::Regular(1, 0),
+                    Tile::Regular(1, 1),
+                    Tile::Regular(2, 1),
+                    Tile::Regular(3, 1),
+                    Tile::Regular(3, 2),
+                    Tile::Regular(3, 3)
+                ]
+            ))
+        );
+        assert_eq!(bits.shortest_path(Colour::Black), None);
+    }
+}
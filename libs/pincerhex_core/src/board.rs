@@ -1,39 +1,113 @@
-use crate::tile::{Colour, PieceState, Tile};
+use crate::{
+    tile::{Colour, PieceState, Tile, MAX_SIZE},
+    zobrist,
+};
 use alloc::{collections::BTreeSet, string::String, vec::Vec};
+use core::str::FromStr;
 
-#[allow(unused_imports)]
-use micromath::F32Ext;
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_field_names)]
 pub struct Board {
     pub size: i8,
     board: Vec<PieceState>,
+    /// Zobrist hash of the stones, updated on every change
+    hash: u64,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error {
     NotInRange,
+    /// A character other than `B`, `W`, `.` or `|`, at the given character position
+    InvalidChar(char, usize),
+    /// A board without row separators whose number of cells is not a square
+    NotSquare(usize),
+    /// A row whose length is not the number of rows
+    RowLength {
+        row: usize,
+        len: usize,
+    },
+    /// Two row separators in a row, at the given row
+    EmptyRow(usize),
+    Empty,
 }
 
-impl From<&str> for Board {
-    // Create board from bot output string. Ex: "...|B.B|.W.|"
-    fn from(compressed: &str) -> Self {
-        let board = compressed
-            .trim()
-            .chars()
-            .filter(|&c| c != '|')
-            .map(|c| match c {
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotInRange => write!(f, "not in range"),
+            Self::InvalidChar(c, pos) => write!(f, "invalid character `{c}` at position {pos}"),
+            Self::NotSquare(cells) => write!(f, "{cells} cells do not make a square board"),
+            Self::RowLength { row, len } => write!(f, "row {} has {len} cells", row + 1),
+            Self::EmptyRow(row) => write!(f, "row {} is empty", row + 1),
+            Self::Empty => write!(f, "empty board"),
+        }
+    }
+}
+
+impl TryFrom<&str> for Board {
+    type Error = Error;
+
+    /// Parses the compressed board output, ex: "...|B.B|.W.|". The row separators are optional.
+    fn try_from(compressed: &str) -> Result<Self, Self::Error> {
+        // Positions in errors count from the start of the untrimmed input
+        let offset = compressed.chars().take_while(|c| c.is_whitespace()).count();
+        let compressed = compressed.trim();
+        let mut board = Vec::with_capacity(compressed.len());
+        for (pos, c) in compressed.chars().enumerate() {
+            board.push(match c {
                 'B' => PieceState::Colour(Colour::Black),
                 'W' => PieceState::Colour(Colour::White),
                 '.' => PieceState::Empty,
-                _ => panic!("Incorrect bot output character`{}`", c),
-            })
-            .collect::<Vec<PieceState>>();
-
-        Self {
-            size: (board.len() as f32).sqrt() as i8,
-            board,
+                '|' => continue,
+                c => return Err(Error::InvalidChar(c, offset + pos)),
+            });
         }
+        if board.is_empty() {
+            return Err(Error::Empty);
+        }
+
+        let size = if compressed.contains('|') {
+            let rows = compressed
+                .strip_suffix('|')
+                .unwrap_or(compressed)
+                .split('|')
+                .map(str::len)
+                .collect::<Vec<_>>();
+            if let Some(row) = rows.iter().position(|&l| l == 0) {
+                return Err(Error::EmptyRow(row));
+            }
+            if let Some((row, &len)) = rows.iter().enumerate().find(|(_, &l)| l != rows.len()) {
+                return Err(Error::RowLength { row, len });
+            }
+            rows.len()
+        } else {
+            let size = (1..=board.len())
+                .find(|s| s * s >= board.len())
+                .unwrap_or_default();
+            if size * size != board.len() {
+                return Err(Error::NotSquare(board.len()));
+            }
+            size
+        };
+        let size = i8::try_from(size)
+            .ok()
+            .filter(|&s| s <= MAX_SIZE)
+            .ok_or(Error::NotInRange)?;
+        let hash = board
+            .iter()
+            .enumerate()
+            .fold(zobrist::empty(size), |hash, (idx, &s)| {
+                hash ^ zobrist::piece(idx, s)
+            });
+        Ok(Self { size, board, hash })
+    }
+}
+
+impl FromStr for Board {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
     }
 }
 
@@ -43,15 +117,23 @@ impl<'a> Board {
         Self {
             size,
             board: { vec![PieceState::Empty; (size as usize).pow(2)] },
+            hash: zobrist::empty(size),
         }
     }
 
+    /// Zobrist hash of the size and stones, the same for equal positions
+    #[must_use]
+    pub const fn zobrist(&self) -> u64 {
+        self.hash
+    }
+
     #[must_use]
     pub fn get(&self, r: i8, c: i8) -> Option<PieceState> {
         self.get_tile(Tile::Regular(r, c))
     }
 
-    #[allow(dead_code)]
+    /// Mirrors the stones along the long diagonal and flips their colours
+    ///
     /// # Errors
     /// Will return an `Err` if a set tile was invalid
     pub fn swap_pieces(&mut self) -> Result<(), Error> {
@@ -157,6 +239,7 @@ impl<'a> Board {
     /// Will return an `Err` if the given tile is not in range of the board
     pub fn set_tile(&mut self, tile: Tile, s: PieceState) -> Result<(), Error> {
         let idx = tile.to_index(self.size).ok_or(Error::NotInRange)?;
+        self.hash ^= zobrist::piece(idx, self.board[idx]) ^ zobrist::piece(idx, s);
         self.board[idx] = s;
         Ok(())
     }
@@ -234,7 +317,7 @@ impl<'a> Iterator for Iter<'a> {
 
 #[cfg(test)]
 mod board_testing {
-    use super::{Board, Colour, PieceState, Tile};
+    use super::{Board, Colour, Error, PieceState, Tile, MAX_SIZE};
 
     macro_rules! set {
         ($board: ident, $row: expr, $col: expr, $colour: expr) => {
@@ -270,4 +353,80 @@ mod board_testing {
 
         assert_eq!(format!("{board}"), expected2);
     }
+
+    #[test]
+    fn parsing() {
+        let board = "B..|.W.|...|".parse::<Board>().unwrap();
+        assert_eq!(board.size, 3);
+        assert_eq!(board.get(1, 1), Some(PieceState::Colour(Colour::White)));
+        assert_eq!(Board::try_from("B...").map(|b| b.size), Ok(2));
+        assert_eq!(
+            Board::try_from(".x.").err(),
+            Some(Error::InvalidChar('x', 1))
+        );
+        assert_eq!(Board::try_from("B....").err(), Some(Error::NotSquare(5)));
+        assert_eq!(
+            Board::try_from("..|...|").err(),
+            Some(Error::RowLength { row: 1, len: 3 })
+        );
+        assert_eq!(Board::try_from("").err(), Some(Error::Empty));
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(
+            Board::try_from("  .x.").err(),
+            Some(Error::InvalidChar('x', 3))
+        );
+        assert_eq!(
+            Board::try_from("B.|é.|").err(),
+            Some(Error::InvalidChar('é', 3))
+        );
+        assert_eq!(Board::try_from(" |||\n").err(), Some(Error::Empty));
+        assert_eq!(
+            Board::try_from("..|..|..|").err(),
+            Some(Error::RowLength { row: 0, len: 2 })
+        );
+        assert_eq!(
+            Board::try_from("...|...|...||").err(),
+            Some(Error::EmptyRow(3))
+        );
+        assert_eq!(Board::try_from("..||..|").err(), Some(Error::EmptyRow(1)));
+        let too_large = ".".repeat((MAX_SIZE as usize + 1).pow(2));
+        assert_eq!(
+            Board::try_from(too_large.as_str()).err(),
+            Some(Error::NotInRange)
+        );
+
+        // Everything that parses prints back in the same form
+        for s in ["B.|.W", " B..W ", "B.W|.B.|W..|", "B..|.W.|...|\n"] {
+            let board = Board::try_from(s).unwrap();
+            let again = Board::try_from(board.get_compressed().as_str()).unwrap();
+            assert_eq!(again.get_compressed(), board.get_compressed());
+            assert_eq!(again.zobrist(), board.zobrist());
+        }
+    }
+
+    #[test]
+    fn zobrist() {
+        let mut board = Board::new(4);
+        let empty = board.zobrist();
+        set!(board, 0, 1, Colour::Black);
+        assert_ne!(board.zobrist(), empty);
+        assert_eq!(
+            Board::try_from(".B..|....|....|....|").map(|b| b.zobrist()),
+            Ok(board.zobrist())
+        );
+
+        board.swap_pieces().unwrap();
+        assert_eq!(
+            Board::try_from("....|W...|....|....|").map(|b| b.zobrist()),
+            Ok(board.zobrist())
+        );
+        board
+            .set_tile(Tile::Regular(1, 0), PieceState::Empty)
+            .unwrap();
+        assert_eq!(board.zobrist(), empty);
+        assert_ne!(Board::new(5).zobrist(), empty);
+    }
 }
@@ -0,0 +1,78 @@
+use crate::{
+    board::Board, eval::PotentialEvaluator, params::EvalParams, tile::Colour, tile::Tile, Rand,
+};
+
+/// Result of an engine search.
+#[derive(Debug, Clone, Copy)]
+pub struct Evaluation {
+    pub mv: Tile,
+    /// Engine specific score of the move, if the engine has one
+    pub score: Option<f32>,
+}
+
+/// Tells an engine when it has to stop searching.
+pub trait Deadline {
+    fn expired(&self) -> bool;
+}
+
+/// A move generator.
+pub trait Engine {
+    /// Finds a move for `active`. `starting` is the colour that played first and `move_count`
+    /// is the number of stones placed on the board by both players so far, not counting swaps.
+    fn best_move(
+        &mut self,
+        board: &Board,
+        active: Colour,
+        starting: Colour,
+        move_count: u16,
+        rng: &mut dyn Rand,
+    ) -> Evaluation;
+
+    /// Same as [`Self::best_move`] but keeps searching until `deadline` expires instead of
+    /// stopping at the engine's own budget. Engines without anytime search return early.
+    #[allow(clippy::too_many_arguments)]
+    fn best_move_until(
+        &mut self,
+        board: &Board,
+        active: Colour,
+        starting: Colour,
+        move_count: u16,
+        rng: &mut dyn Rand,
+        deadline: &dyn Deadline,
+    ) -> Evaluation {
+        let _ = deadline;
+        self.best_move(board, active, starting, move_count, rng)
+    }
+}
+
+/// Single pass of the [`PotentialEvaluator`] with no lookahead.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PotentialEngine {
+    params: EvalParams,
+}
+
+impl PotentialEngine {
+    #[must_use]
+    pub const fn new(params: EvalParams) -> Self {
+        Self { params }
+    }
+}
+
+impl Engine for PotentialEngine {
+    fn best_move(
+        &mut self,
+        board: &Board,
+        active: Colour,
+        starting: Colour,
+        move_count: u16,
+        rng: &mut dyn Rand,
+    ) -> Evaluation {
+        let ((i, j), score) = PotentialEvaluator::new(board, active, starting, &self.params)
+            .evaluate()
+            .get_scored_best_move(move_count, rng);
+        Evaluation {
+            mv: Tile::Regular(i, j),
+            score: Some(score),
+        }
+    }
+}
@@ -5,7 +5,9 @@ use alloc::vec::Vec;
 use micromath::F32Ext;
 
 use crate::{
+    bitboard::Bitboard,
     board::Board,
+    params::EvalParams,
     tile::{Colour, PieceState, Tile},
     Rand,
 };
@@ -37,60 +39,42 @@ impl Edge {
 
 pub struct PotentialEvaluator<'a> {
     board: &'a Board,
+    /// Same position as `board`, for its precomputed neighbour masks
+    bits: Option<&'a Bitboard>,
     active: Colour,
     starting: Colour,
     potential: Vec<[i32; 4]>,
     bridge: Vec<[f32; 4]>,
     update: Vec<bool>,
+    params: &'a EvalParams,
 }
 
-struct Params {
-    rounds: i32,
-
-    init_potential: i32,
-    default_potential: i32,
-    diff: i32,
-    max_value: i32,
-
-    /// Black Factor for FF.
-    bf: f32,
-    /// White Factor for FF.
-    wf: f32,
-
-    pp_threshold: i32,
-    mmp_deduction: f32,
-    move_mul: f32,
-}
-
-const PARAMS: Params = Params {
-    bf: 140.0,
-    wf: 1.0,
-    init_potential: 20_000,
-    default_potential: 128,
-    diff: 140,
-    max_value: 30_000,
-    rounds: 1000,
-    pp_threshold: 268,
-    move_mul: 8.0,
-    mmp_deduction: 400.0,
-};
-
 const EDGES: [Edge; 4] = [Edge::Left, Edge::Right, Edge::Top, Edge::Bottom];
 
 impl<'a> PotentialEvaluator<'a> {
     #[must_use]
-    pub fn new(board: &'a Board, active: Colour, starting: Colour) -> Self {
+    pub fn new(board: &'a Board, active: Colour, starting: Colour, params: &'a EvalParams) -> Self {
         let size = board.size as usize;
         Self {
             board,
+            bits: None,
             active,
-            potential: vec![[PARAMS.init_potential; 4]; size.pow(2)],
+            potential: vec![[params.init_potential; 4]; size.pow(2)],
             starting,
             bridge: vec![[0.; 4]; size.pow(2)],
             update: vec![false; size.pow(2)],
+            params,
         }
     }
 
+    /// Uses the neighbour masks of `bits`, which must hold the same position as the board,
+    /// instead of looking neighbours up on the board
+    #[must_use]
+    pub const fn with_bitboard(mut self, bits: &'a Bitboard) -> Self {
+        self.bits = Some(bits);
+        self
+    }
+
     fn reset_update(&mut self) {
         for u in &mut self.update {
             *u = true;
@@ -107,7 +91,7 @@ impl<'a> PotentialEvaluator<'a> {
 
     fn evaluate_side(&mut self, edge: Edge) {
         self.reset_update();
-        for _i in 1..PARAMS.rounds {
+        for _i in 1..self.params.rounds {
             let mut set = 0;
             for (tile, state) in self.board.iter() {
                 if self.update[tile.to_index(self.board.size).unwrap()] {
@@ -149,7 +133,8 @@ impl<'a> PotentialEvaluator<'a> {
             self.bridge[index][edge.idx()] /= 2.;
         }
 
-        self.bridge[index][edge.idx()] = f32::min(self.bridge[index][edge.idx()], 68.);
+        self.bridge[index][edge.idx()] =
+            f32::min(self.bridge[index][edge.idx()], self.params.max_bridge);
 
         match self.board.get_tile(tile) {
             Some(PieceState::Colour(c)) if c == edge.colour() => {
@@ -161,8 +146,10 @@ impl<'a> PotentialEvaluator<'a> {
                     0
                 }
             }
-            Some(_) | None if min_potential + PARAMS.diff < self.potential[index][edge.idx()] => {
-                self.potential[index][edge.idx()] = min_potential + PARAMS.diff;
+            Some(_) | None
+                if min_potential + self.params.diff < self.potential[index][edge.idx()] =>
+            {
+                self.potential[index][edge.idx()] = min_potential + self.params.diff;
                 self.update_neighbours(tile);
                 1
             }
@@ -173,7 +160,7 @@ impl<'a> PotentialEvaluator<'a> {
     fn calculate_potential(&mut self, tile: Tile, edge: Edge) -> (i32, i32) {
         let mut block_score = 0;
         let mut bridge_weights = [0; 6];
-        let mut min_potential = PARAMS.max_value;
+        let mut min_potential = self.params.max_value;
         let mut neighbours = [0; 6];
 
         for (idx, value) in self
@@ -188,18 +175,21 @@ impl<'a> PotentialEvaluator<'a> {
 
         for idx in 0..6 {
             let value = neighbours[idx];
-            if value >= PARAMS.max_value && neighbours[(idx + 2) % 6] >= PARAMS.max_value {
+            if value >= self.params.max_value && neighbours[(idx + 2) % 6] >= self.params.max_value
+            {
                 if neighbours[(idx + 1) % 6] < 0 {
                     block_score += 32;
                 } else {
-                    neighbours[(idx + 1) % 6] += 128; // 512
+                    neighbours[(idx + 1) % 6] += self.params.neighbour_penalty; // 512
                 }
             }
         }
 
         for idx in 0..6 {
             let value = neighbours[idx];
-            if (value >= PARAMS.max_value) && neighbours[(idx + 3) % 6] >= PARAMS.max_value {
+            if (value >= self.params.max_value)
+                && neighbours[(idx + 3) % 6] >= self.params.max_value
+            {
                 block_score += 30;
             }
         }
@@ -207,7 +197,7 @@ impl<'a> PotentialEvaluator<'a> {
         for idx in 0..6 {
             let value = neighbours[idx];
             if value < 0 {
-                neighbours[idx] += PARAMS.max_value;
+                neighbours[idx] += self.params.max_value;
                 bridge_weights[idx] = 10;
             } else {
                 bridge_weights[idx] = 1;
@@ -244,9 +234,9 @@ impl<'a> PotentialEvaluator<'a> {
         }
 
         let edge_bridge_score = if edge.colour() == self.active {
-            66.
+            self.params.active_bridge
         } else {
-            52.
+            self.params.opponent_bridge
         };
         let mut bridge_score = total_weight / 5.;
         if (2. ..10.).contains(&total_weight) {
@@ -255,14 +245,14 @@ impl<'a> PotentialEvaluator<'a> {
         }
 
         if total_weight < 2. {
-            let mut closest_high_value = PARAMS.max_value;
+            let mut closest_high_value = self.params.max_value;
             for &val in neighbours.iter() {
                 if val > min_potential && closest_high_value > val {
                     closest_high_value = val;
                 }
             }
 
-            if closest_high_value <= min_potential + 104 {
+            if closest_high_value <= min_potential + self.params.bridge_range {
                 bridge_score = edge_bridge_score - (closest_high_value - min_potential) as f32 / 4.;
                 min_potential -= 64;
             }
@@ -275,6 +265,12 @@ impl<'a> PotentialEvaluator<'a> {
     }
 
     fn update_neighbours(&mut self, tile: Tile) {
+        if let Some(bits) = self.bits {
+            for idx in bits.neighbours(tile) {
+                self.update[idx] = true;
+            }
+            return;
+        }
         for n in self.board.neighbours(tile) {
             if let Some(idx) = n.and_then(|t| t.0.to_index(self.board.size)) {
                 self.update[idx] = true;
@@ -285,11 +281,13 @@ impl<'a> PotentialEvaluator<'a> {
     fn pot_val(&self, tile: Option<(Tile, PieceState)>, edge: Edge) -> i32 {
         match tile {
             Some((_, PieceState::Colour(other))) if other == edge.colour().opponent() => {
-                PARAMS.max_value
+                self.params.max_value
             } // Blocked
             Some((Tile::Regular(r, c), PieceState::Empty)) => self.get_potential(r, c, edge),
-            Some((Tile::Regular(r, c), _)) => self.get_potential(r, c, edge) - PARAMS.max_value,
-            Some((_, _)) | None => PARAMS.max_value, // Border
+            Some((Tile::Regular(r, c), _)) => {
+                self.get_potential(r, c, edge) - self.params.max_value
+            }
+            Some((_, _)) | None => self.params.max_value, // Border
         }
     }
 
@@ -302,18 +300,56 @@ impl<'a> PotentialEvaluator<'a> {
 
     /// # Panics
     /// Panics if there was an invalid tile
-    pub fn get_best_move(&self, move_count: u16, rng: &mut impl Rand) -> (i8, i8) {
+    pub fn get_best_move(&self, move_count: u16, rng: &mut (impl Rand + ?Sized)) -> (i8, i8) {
+        self.get_scored_best_move(move_count, rng).0
+    }
+
+    /// Same as [`Self::get_best_move`] but also returns the score of the move. Lower scores are
+    /// better.
+    ///
+    /// # Panics
+    /// Panics if there was an invalid tile
+    pub fn get_scored_best_move(
+        &self,
+        move_count: u16,
+        rng: &mut (impl Rand + ?Sized),
+    ) -> ((i8, i8), f32) {
+        self.get_move_scores(move_count, rng)
+            .into_iter()
+            .fold(
+                None,
+                |best: Option<((i8, i8), f32)>, (mv, mmp)| match best {
+                    Some((_, mm)) if mmp >= mm => best,
+                    Some(_) | None => Some((mv, mmp)),
+                },
+            )
+            .expect("finding the best move")
+    }
+
+    /// Scores every empty tile on the board. Lower scores are better moves.
+    ///
+    /// `move_count` is the number of stones placed by both players, as in
+    /// [`crate::Engine::best_move`]. The pull towards the centre, `bf` or `wf` over its square,
+    /// fades as it grows, so it fades about twice as fast as it would counting one player's
+    /// moves.
+    ///
+    /// # Panics
+    /// Panics if there was an invalid tile
+    pub fn get_move_scores(
+        &self,
+        move_count: u16,
+        rng: &mut (impl Rand + ?Sized),
+    ) -> Vec<((i8, i8), f32)> {
         let mut ff: f32 = 0.0;
-        let mut mm: f32 = f32::MAX;
         let (iq, jq) = self.get_quadrant();
-        let mut best_move: Option<(i8, i8)> = None;
+        let mut scores = Vec::new();
 
         if move_count > 0 {
             let colour = self.starting;
             let factor = if colour == Colour::Black {
-                PARAMS.bf
+                self.params.bf
             } else {
-                PARAMS.wf
+                self.params.wf
             };
             let m = move_count as usize;
             ff = factor / (m * m) as f32;
@@ -331,7 +367,7 @@ impl<'a> PotentialEvaluator<'a> {
                 let mut mmp = (((f32::from(i) - f_size).abs() + (f32::from(j) - f_size).abs())
                     * ff)
                     + rng.next();
-                mmp += PARAMS.move_mul
+                mmp += self.params.move_mul
                     * f32::from(
                         (iq * (i - self.board.size / 2)) + (jq * (j - self.board.size / 2)),
                     )
@@ -347,18 +383,32 @@ impl<'a> PotentialEvaluator<'a> {
                 let pp1 = self.potential[index][2] + self.potential[index][3];
                 mmp += (pp0 + pp1) as f32;
 
-                if pp0 <= PARAMS.pp_threshold || pp1 <= PARAMS.pp_threshold {
-                    mmp -= PARAMS.mmp_deduction;
+                if pp0 <= self.params.pp_threshold || pp1 <= self.params.pp_threshold {
+                    mmp -= self.params.mmp_deduction;
                 }
 
-                if mmp < mm {
-                    mm = mmp;
-                    best_move = Some((i, j));
-                }
+                scores.push(((i, j), mmp));
             }
         }
 
-        best_move.expect("finding the best move")
+        scores
+    }
+
+    /// Lowest combined potential to both of `colour`'s edges over every tile it can still use.
+    /// The lower the potential the closer `colour` is to connecting its edges.
+    #[must_use]
+    pub fn get_colour_potential(&self, colour: Colour) -> i32 {
+        let edges = match colour {
+            Colour::Black => [Edge::Top, Edge::Bottom],
+            Colour::White => [Edge::Left, Edge::Right],
+        };
+        self.board
+            .iter()
+            .filter(|(_, state)| *state != PieceState::Colour(colour.opponent()))
+            .filter_map(|(tile, _)| tile.to_index(self.board.size))
+            .map(|idx| self.potential[idx][edges[0].idx()] + self.potential[idx][edges[1].idx()])
+            .min()
+            .unwrap_or(self.params.max_value)
     }
 
     fn get_quadrant(&self) -> (i8, i8) {
@@ -395,7 +445,7 @@ impl<'a> PotentialEvaluator<'a> {
                         self.potential[index][e.idx()] = 0;
                     }
                     _ => {
-                        self.potential[index][e.idx()] = PARAMS.default_potential;
+                        self.potential[index][e.idx()] = self.params.default_potential;
                     }
                 }
             }
@@ -13,13 +13,22 @@
 #[macro_use]
 extern crate alloc;
 
+mod bitboard;
 mod board;
+mod engine;
 mod eval;
+mod params;
 mod tile;
+pub mod zobrist;
 
+pub use bitboard::Bitboard;
 pub use board::{Board, Error as BoardError};
+pub use engine::{Deadline, Engine, Evaluation, PotentialEngine};
 pub use eval::PotentialEvaluator;
-pub use tile::{Colour, Error as TileError, Move, PieceState, Tile};
+pub use params::{Error as ParamsError, EvalParams};
+pub use tile::{
+    parse_letters, Colour, Error as TileError, Letters, Move, PieceState, Tile, MAX_SIZE,
+};
 
 pub trait Rand {
     fn in_range(&mut self, a: i8, b: i8) -> i8;
@@ -28,12 +37,15 @@ pub trait Rand {
 
 const NO_SWAP_CHANCE: i8 = 2;
 
-pub fn should_swap(r: i8, c: i8, size: i8, rand: &mut impl Rand) -> bool {
+/// Smallest board size that [`first_move`] can open on
+pub const MIN_OPENING_SIZE: i8 = 4;
+
+pub fn should_swap(r: i8, c: i8, size: i8, rand: &mut (impl Rand + ?Sized)) -> bool {
     ((r + c < 2) || (r + c > 2 * size - 4))
         || (((r + c == 2) || (r + c == 2 * size - 4)) && rand.in_range(0, NO_SWAP_CHANCE) == 0)
 }
 
-pub fn first_move(size: i8, rand: &mut impl Rand) -> (i8, i8) {
+pub fn first_move(size: i8, rand: &mut (impl Rand + ?Sized)) -> (i8, i8) {
     let (mut i, mut j) = (
         rand.in_range(0, size / 2 - 1),
         rand.in_range(0, size / 2 - 1),
@@ -0,0 +1,179 @@
+#[allow(unused_imports)]
+use micromath::F32Ext;
+
+/// A value that can be read and written as an `f32`, rounding integers.
+trait Param: Copy {
+    fn from_f32(value: f32) -> Self;
+    fn to_f32(self) -> f32;
+}
+
+impl Param for i32 {
+    fn from_f32(value: f32) -> Self {
+        value.round() as Self
+    }
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+}
+
+impl Param for f32 {
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+}
+
+macro_rules! params {
+    ($($(#[doc = $doc:literal])* $name:ident: $ty:ty = $default:expr,)*) => {
+        /// Tunable parameters of the [`PotentialEvaluator`](crate::PotentialEvaluator).
+        ///
+        /// Can be read from and written to a simple `name = value` format, one parameter per
+        /// line. Parameters that are left out keep their default value.
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub struct EvalParams {
+            $($(#[doc = $doc])* pub $name: $ty,)*
+        }
+
+        impl EvalParams {
+            pub const DEFAULT: Self = Self { $($name: $default,)* };
+
+            /// Names of every parameter
+            pub const NAMES: &'static [&'static str] = &[$(stringify!($name),)*];
+
+            #[must_use]
+            pub fn get(&self, name: &str) -> Option<f32> {
+                match name {
+                    $(stringify!($name) => Some(Param::to_f32(self.$name)),)*
+                    _ => None,
+                }
+            }
+
+            /// Sets a parameter by name. Integer parameters are rounded. Returns `false` if
+            /// there is no such parameter.
+            pub fn set(&mut self, name: &str, value: f32) -> bool {
+                match name {
+                    $(stringify!($name) => self.$name = <$ty as Param>::from_f32(value),)*
+                    _ => return false,
+                }
+                true
+            }
+        }
+    };
+}
+
+params! {
+    rounds: i32 = 1000,
+
+    init_potential: i32 = 20_000,
+    default_potential: i32 = 128,
+    diff: i32 = 140,
+    max_value: i32 = 30_000,
+
+    /// Black Factor for FF.
+    bf: f32 = 140.0,
+    /// White Factor for FF.
+    wf: f32 = 1.0,
+
+    pp_threshold: i32 = 268,
+    mmp_deduction: f32 = 400.0,
+    move_mul: f32 = 8.0,
+
+    /// Added to a neighbour between two blocked tiles.
+    neighbour_penalty: i32 = 128,
+    /// Bridge score for the active colour's edges.
+    active_bridge: f32 = 66.0,
+    /// Bridge score for the opponent's edges.
+    opponent_bridge: f32 = 52.0,
+    /// Upper bound of a tile's bridge score.
+    max_bridge: f32 = 68.0,
+    /// How much higher a neighbour's potential may be to still count as a bridge.
+    bridge_range: i32 = 104,
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// Line is not of the form `name = value`
+    InvalidLine(usize),
+    UnknownParam(usize),
+    InvalidValue(usize),
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidLine(l) => write!(f, "line {l}: expected `name = value`"),
+            Self::UnknownParam(l) => write!(f, "line {l}: unknown parameter"),
+            Self::InvalidValue(l) => write!(f, "line {l}: invalid value"),
+        }
+    }
+}
+
+impl core::str::FromStr for EvalParams {
+    type Err = Error;
+
+    /// Parses `name = value` lines. Empty lines and lines starting with `#` are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Self::default();
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, value) = line.split_once('=').ok_or(Error::InvalidLine(idx + 1))?;
+            let value = value
+                .trim()
+                .parse::<f32>()
+                .map_err(|_| Error::InvalidValue(idx + 1))?;
+            if !params.set(name.trim(), value) {
+                return Err(Error::UnknownParam(idx + 1));
+            }
+        }
+        Ok(params)
+    }
+}
+
+impl core::fmt::Display for EvalParams {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for name in Self::NAMES {
+            if let Some(value) = self.get(name) {
+                writeln!(f, "{name} = {value}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::EvalParams;
+
+    #[test]
+    fn round_trip() {
+        let mut params = EvalParams::default();
+        assert!(params.set("rounds", 12.4));
+        assert!(params.set("bf", 3.5));
+        assert!(!params.set("nope", 1.));
+
+        let parsed = format!("{params}").parse::<EvalParams>().unwrap();
+        assert_eq!(parsed, params);
+        assert_eq!(parsed.rounds, 12);
+
+        let partial = "# comment\n\nwf = 2\n".parse::<EvalParams>().unwrap();
+        assert_eq!(partial.get("wf"), Some(2.));
+        assert_eq!(partial.rounds, EvalParams::DEFAULT.rounds);
+
+        assert!("wf 2".parse::<EvalParams>().is_err());
+        assert!("foo = 2".parse::<EvalParams>().is_err());
+        assert!("wf = x".parse::<EvalParams>().is_err());
+    }
+}
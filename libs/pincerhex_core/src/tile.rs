@@ -9,6 +9,7 @@ pub enum Colour {
     White,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Move {
     Move(Tile),
     Swap,
@@ -91,10 +92,59 @@ impl Tile {
     }
 }
 
+/// Largest supported board size
+pub const MAX_SIZE: i8 = 32;
+
+/// Length of the longest coordinate on a board of `MAX_SIZE`, `af32`
+const MAX_COORDINATE_LEN: usize = 4;
+
+/// Displays an index as letters: `a` to `z`, then `aa`, `ab` and so on
+pub struct Letters(pub i8);
+
+impl core::fmt::Display for Letters {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let Ok(mut n) = u8::try_from(self.0) else {
+            return f.write_str("invalid");
+        };
+        // An i8 needs at most two letters
+        let mut letters = [0; 2];
+        let mut len = 0;
+        loop {
+            letters[len] = b'a' + n % 26;
+            len += 1;
+            if n < 26 {
+                break;
+            }
+            n = n / 26 - 1;
+        }
+        letters[..len]
+            .iter()
+            .rev()
+            .try_for_each(|&l| f.write_char(char::from(l)))
+    }
+}
+
+/// Parses the letters written by [`Letters`]
+#[must_use]
+pub fn parse_letters(s: &str) -> Option<i8> {
+    if s.is_empty() {
+        return None;
+    }
+    let mut n = 0_u32;
+    for b in s.bytes() {
+        if !b.is_ascii_lowercase() {
+            return None;
+        }
+        n = n.checked_mul(26)?.checked_add(u32::from(b - b'a') + 1)?;
+    }
+    i8::try_from(n - 1).ok()
+}
+
 #[derive(Debug)]
 pub enum Error {
     InvalidCol,
     InvalidRow,
+    TooLong,
 }
 
 impl core::fmt::Display for Error {
@@ -102,6 +152,7 @@ impl core::fmt::Display for Error {
         match self {
             Self::InvalidCol => write!(f, "invalid col"),
             Self::InvalidRow => write!(f, "invalid row"),
+            Self::TooLong => write!(f, "coordinate too long"),
         }
     }
 }
@@ -109,9 +160,20 @@ impl core::fmt::Display for Error {
 impl TryFrom<&str> for Tile {
     type Error = Error;
 
+    /// Parses the row as letters followed by the column as a number, ex: `aa12`
     fn try_from(s: &str) -> Result<Self, Self::Error> {
-        let r = s.chars().next().ok_or(Error::InvalidRow)? as i8 - 97; // First letter
-        let c = s[1..].parse::<i8>().map_err(|_| Error::InvalidCol)? - 1; // All following digits
+        if s.len() > MAX_COORDINATE_LEN {
+            return Err(Error::TooLong);
+        }
+        let letters = s.bytes().take_while(u8::is_ascii_lowercase).count();
+        let r = parse_letters(&s[..letters]).ok_or(Error::InvalidRow)?;
+        let digits = &s[letters..];
+        let c = Some(digits)
+            .filter(|d| !d.is_empty() && d.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|d| d.parse::<i8>().ok())
+            .filter(|&c| c > 0)
+            .ok_or(Error::InvalidCol)?
+            - 1;
         Ok(Self::Regular(r, c))
     }
 }
@@ -119,10 +181,7 @@ impl TryFrom<&str> for Tile {
 impl core::fmt::Display for Tile {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match &self {
-            Self::Regular(row, col) => {
-                f.write_char((row + 97) as u8 as char)?;
-                f.write_fmt(format_args!("{}", col + 1))
-            }
+            Self::Regular(row, col) => write!(f, "{}{}", Letters(*row), col + 1),
             Self::Edge1 => f.write_str("edge1"),
             Self::Edge2 => f.write_str("edge2"),
             Self::Invalid => f.write_str("invalid"),
@@ -138,3 +197,24 @@ impl core::fmt::Display for Colour {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn coordinates() {
+        for (tile, s) in [
+            (Tile::Regular(0, 0), "a1"),
+            (Tile::Regular(25, 31), "z32"),
+            (Tile::Regular(26, 11), "aa12"),
+            (Tile::Regular(31, 31), "af32"),
+        ] {
+            assert_eq!(Tile::try_from(s).ok(), Some(tile));
+            assert_eq!(format!("{tile}"), s);
+        }
+        for s in ["", "a", "1", "a0", "a+1", "ab1c", "é1", "aaaaa1"] {
+            assert!(Tile::try_from(s).is_err(), "{s}");
+        }
+    }
+}
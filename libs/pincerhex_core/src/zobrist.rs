@@ -0,0 +1,48 @@
+//! Keys for Zobrist hashing. They are generated at compile time from a fixed seed, so hashes
+//! are the same across runs and can be stored.
+
+use crate::tile::{PieceState, MAX_SIZE};
+
+const CELLS: usize = MAX_SIZE as usize * MAX_SIZE as usize;
+const SEED: u64 = 0x7069_6e63_6572_6878;
+
+const fn splitmix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Key of every cell for black and white
+static KEYS: [[u64; CELLS]; 2] = {
+    let mut keys = [[0; CELLS]; 2];
+    let mut state = SEED;
+    let mut i = 0;
+    while i < CELLS {
+        state = splitmix(state);
+        keys[0][i] = state;
+        state = splitmix(state);
+        keys[1][i] = state;
+        i += 1;
+    }
+    keys
+};
+
+/// Xored into a position's hash when white is to play
+pub const WHITE_TO_PLAY: u64 = splitmix(SEED ^ 1);
+
+/// Hash of an empty board, which differs between sizes
+#[must_use]
+pub const fn empty(size: i8) -> u64 {
+    splitmix(SEED ^ ((size as u64) << 8))
+}
+
+/// Key of `piece` at the board index `idx`. Empty cells and boards larger than `MAX_SIZE` have
+/// no key.
+#[must_use]
+pub fn piece(idx: usize, piece: PieceState) -> u64 {
+    match piece {
+        PieceState::Colour(c) => KEYS[c.group_idx()].get(idx).copied().unwrap_or_default(),
+        PieceState::Empty => 0,
+    }
+}
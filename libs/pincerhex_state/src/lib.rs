@@ -13,11 +13,14 @@
 #[macro_use]
 extern crate alloc;
 
+pub mod notation;
+pub mod sgf;
 mod state;
 mod union_find;
 
 pub use state::*;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Winner {
     Bot,
     Opponent,
@@ -0,0 +1,238 @@
+//! Move lists as pasted from online sites.
+//!
+//! Cells use the column as a letter and the row as a number, like `HexGui`. Moves alternate
+//! between the colours, starting with black.
+//!
+//! * Little Golem: moves separated by spaces or commas, optionally numbered (`1.a1 2.swap 3.b3`).
+//!   The swap switches sides.
+//! * `HexWorld`: URLs like `https://hexworld.org/board/#11c1,a1:sb3`, with the moves written
+//!   without separators and `:s` for the swap, which mirrors the opening stone.
+//!
+//! A game whose swap was played the other way is written mirrored along the long diagonal after
+//! the swap, which gives the same game with the colours flipped.
+
+use alloc::string::String;
+use core::fmt::Write;
+
+use pincerhex_core::{parse_letters, Letters, Move, PieceState, Tile, MAX_SIZE};
+
+use crate::{Error as StateError, HistoryEntry, State, SwapKind};
+
+const HEXWORLD_URL: &str = "https://hexworld.org/board/";
+
+/// Displays a cell with the column as a letter and the row as a number
+pub struct Cell(pub Tile);
+
+impl core::fmt::Display for Cell {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.0 {
+            Tile::Regular(row, col) => write!(f, "{}{}", Letters(col), row + 1),
+            _ => write!(f, "invalid"),
+        }
+    }
+}
+
+/// Parses a cell with the column as letters and the row as a number, ex: `aa12`
+#[must_use]
+pub fn parse_cell(s: &str, size: i8) -> Option<Tile> {
+    let letters = s.bytes().take_while(u8::is_ascii_lowercase).count();
+    let col = parse_letters(&s[..letters])?;
+    let digits = &s[letters..];
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let row = digits.parse::<i8>().ok()? - 1;
+    ((0..size).contains(&row) && (0..size).contains(&col)).then_some(Tile::Regular(row, col))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    InvalidSize,
+    /// The given move number could not be read
+    InvalidMove(usize),
+    /// The given move number could not be played
+    IllegalMove(usize, StateError),
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidSize => write!(f, "invalid board size"),
+            Self::InvalidMove(n) => write!(f, "invalid move {n}"),
+            Self::IllegalMove(n, e) => write!(f, "illegal move {n}: {e}"),
+        }
+    }
+}
+
+fn play(state: &mut State, number: usize, mv: Option<Move>) -> Result<(), Error> {
+    match mv.ok_or(Error::InvalidMove(number))? {
+        Move::Move(t) => {
+            let colour = PieceState::Colour(state.active());
+            state.try_place_piece(t, colour)
+        }
+        Move::Swap => state.swap(),
+    }
+    .map_err(|e| Error::IllegalMove(number, e))
+}
+
+/// Plays a Little Golem move list on a new board
+///
+/// # Errors
+/// Will return an `Err` if a move could not be read or played
+pub fn from_little_golem(s: &str, size: i8) -> Result<State, Error> {
+    let mut state = State::new(size);
+    state.set_swap_kind(SwapKind::Sides);
+    let moves = s
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .map(|m| m.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.'))
+        .filter(|m| !m.is_empty());
+    for (idx, mv) in moves.enumerate() {
+        let mv = match mv.to_lowercase().as_str() {
+            "swap" => Some(Move::Swap),
+            "resign" => break,
+            m => parse_cell(m, size).map(Move::Move),
+        };
+        play(&mut state, idx + 1, mv)?;
+    }
+    Ok(state)
+}
+
+/// Moves of the game, without cleared tiles which have no notation
+fn recorded(state: &State) -> impl Iterator<Item = &HistoryEntry> {
+    state
+        .history()
+        .iter()
+        .filter(|e| !matches!((e.mv, e.piece), (Move::Move(_), PieceState::Empty)))
+}
+
+/// Moves of the game for a notation whose swap is played as `kind`.
+///
+/// Playing the swap the other way gives the transposed game with the colours flipped, so the
+/// moves after such a swap are mirrored to keep them alternating from the swapped position.
+fn converted(state: &State, kind: SwapKind) -> impl Iterator<Item = Move> + '_ {
+    let mut mirrored = false;
+    recorded(state).map(move |entry| match entry.mv {
+        Move::Move(Tile::Regular(row, col)) if mirrored => Move::Move(Tile::Regular(col, row)),
+        Move::Move(t) => Move::Move(t),
+        Move::Swap => {
+            mirrored = entry.swap_kind().is_some_and(|k| k != kind);
+            Move::Swap
+        }
+    })
+}
+
+/// Writes the moves of `state` as a Little Golem move list
+#[must_use]
+pub fn to_little_golem(state: &State) -> String {
+    let mut out = String::new();
+    for mv in converted(state, SwapKind::Sides) {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        let _ = match mv {
+            Move::Move(t) => write!(out, "{}", Cell(t)),
+            Move::Swap => write!(out, "swap"),
+        };
+    }
+    out
+}
+
+/// Plays the moves of a `HexWorld` URL, or just the part after the `#`, on a new board
+///
+/// # Errors
+/// Will return an `Err` if the size or a move could not be read, or a move could not be played
+pub fn from_hexworld(s: &str) -> Result<State, Error> {
+    let s = s.trim();
+    let s = s.rsplit_once('#').map_or(s, |(_, fragment)| fragment);
+    // The size is followed by the variant, like `11c1`
+    let (header, moves) = s.split_once(',').unwrap_or((s, ""));
+    let digits = header.bytes().take_while(u8::is_ascii_digit).count();
+    let size = header[..digits]
+        .parse::<i8>()
+        .ok()
+        .filter(|s| (1..=MAX_SIZE).contains(s))
+        .ok_or(Error::InvalidSize)?;
+
+    let mut state = State::new(size);
+    state.set_swap_kind(SwapKind::Pieces);
+    let mut rest = moves.trim();
+    let mut number = 1;
+    while !rest.is_empty() {
+        let (mv, len) = if let Some(special) = rest.strip_prefix(':') {
+            match special.chars().next() {
+                Some('s') => (Some(Move::Swap), 2),
+                Some('r') => break,
+                _ => (None, 1),
+            }
+        } else {
+            let letters = rest.bytes().take_while(u8::is_ascii_lowercase).count();
+            let len = letters
+                + rest[letters..]
+                    .bytes()
+                    .take_while(u8::is_ascii_digit)
+                    .count();
+            (parse_cell(&rest[..len], size).map(Move::Move), len)
+        };
+        play(&mut state, number, mv)?;
+        rest = &rest[len..];
+        number += 1;
+    }
+    Ok(state)
+}
+
+/// Writes a `HexWorld` URL for the game in `state`
+#[must_use]
+pub fn to_hexworld(state: &State) -> String {
+    let mut out = format!("{HEXWORLD_URL}#{}c1,", state.get_board().size);
+    for mv in converted(state, SwapKind::Pieces) {
+        let _ = match mv {
+            Move::Move(t) => write!(out, "{}", Cell(t)),
+            Move::Swap => write!(out, ":s"),
+        };
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pincerhex_core::Colour;
+
+    #[test]
+    fn move_lists() {
+        let mut state = from_little_golem("1.b1 2.swap, 3.c2 4.a3", 3).unwrap();
+        assert_eq!(state.get_compressed(), ".B.|..W|B..|");
+        assert_eq!(state.active(), Colour::White);
+        assert_eq!(to_little_golem(&state), "b1 swap c2 a3");
+        assert_eq!(
+            from_little_golem("a1 a1", 3).err(),
+            Some(Error::IllegalMove(2, StateError::TileNotEmpty))
+        );
+
+        state = from_hexworld("https://hexworld.org/board/#3c1,b1:sc2a3").unwrap();
+        assert_eq!(state.get_compressed(), "...|W.B|W..|");
+        assert_eq!(
+            to_hexworld(&state),
+            "https://hexworld.org/board/#3c1,b1:sc2a3"
+        );
+        assert_eq!(
+            from_hexworld("#3c1,b1d9").err(),
+            Some(Error::InvalidMove(2))
+        );
+    }
+
+    #[test]
+    fn converts_the_swap() {
+        let golem = from_little_golem("b1 swap c2 a3", 3).unwrap();
+        let url = to_hexworld(&golem);
+        assert_eq!(url, "https://hexworld.org/board/#3c1,b1:sb3c1");
+        let world = from_hexworld(&url).unwrap();
+        let mut expected = golem.get_board().clone();
+        expected.swap_pieces().unwrap();
+        assert_eq!(world.get_compressed(), expected.get_compressed());
+        assert_eq!(world.active(), golem.active().opponent());
+
+        let back = from_little_golem(&to_little_golem(&world), 3).unwrap();
+        assert_eq!(back.get_compressed(), golem.get_compressed());
+    }
+}
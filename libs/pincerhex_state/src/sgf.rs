@@ -0,0 +1,439 @@
+//! Smart Game Format records for Hex (`FF[4]`, `GM[11]`), as read and written by `HexGui`.
+//!
+//! Cells are written with the column as a letter and the row as a number, and the swap is a
+//! move of its own, `swap-sides` or `swap-pieces`.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt::Write, str::FromStr};
+
+use pincerhex_core::{Colour, Move, PieceState, Tile, MAX_SIZE};
+
+use crate::{
+    notation::{parse_cell, Cell},
+    Error as StateError, State, SwapKind,
+};
+
+/// Game type of Hex
+const GAME: &str = "11";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SgfMove {
+    Tile(Tile),
+    Swap(SwapKind),
+    Resign,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub colour: Colour,
+    pub mv: SgfMove,
+    pub comment: Option<String>,
+}
+
+/// The main line of a game record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sgf {
+    pub size: i8,
+    /// Name of the black player
+    pub black: Option<String>,
+    /// Name of the white player
+    pub white: Option<String>,
+    /// Result such as `B+` or `W+Resign`
+    pub result: Option<String>,
+    /// Comment on the whole game
+    pub comment: Option<String>,
+    pub moves: Vec<Node>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Malformed SGF at the given byte offset
+    Syntax(usize),
+    /// The record is not a Hex game
+    NotHex,
+    InvalidSize,
+    /// The given move number is not a cell, swap or resignation
+    InvalidMove(usize),
+    /// The given move number could not be played
+    IllegalMove(usize, StateError),
+    /// A stone mirrored by `swap-pieces` was taken off the given tile
+    MirroredRemoval(Tile),
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Syntax(pos) => write!(f, "syntax error at byte {pos}"),
+            Self::NotHex => write!(f, "not a hex game"),
+            Self::InvalidSize => write!(f, "invalid board size"),
+            Self::InvalidMove(n) => write!(f, "invalid move {n}"),
+            Self::IllegalMove(n, e) => write!(f, "illegal move {n}: {e}"),
+            Self::MirroredRemoval(t) => {
+                write!(
+                    f,
+                    "cannot record removing the swapped stone at {}",
+                    Cell(*t)
+                )
+            }
+        }
+    }
+}
+
+impl Sgf {
+    /// Records the moves played in `state`, with the result if the game was won. A stone that
+    /// was taken off or played over is left out along with the move that placed it, so the
+    /// record plays out to the same board.
+    ///
+    /// # Errors
+    /// Will return an `Err` if a stone that was mirrored by `swap-pieces` is taken off or
+    /// played over, which the record cannot express
+    pub fn from_state(state: &State) -> Result<Self, Error> {
+        let mut moves: Vec<Node> = Vec::new();
+        for entry in state.history() {
+            let (colour, mv) = match (entry.mv, entry.piece) {
+                (Move::Move(_), piece) if piece == entry.previous() => continue,
+                (Move::Move(t), piece) => {
+                    // Moves from before the last swap-pieces were mirrored to other tiles
+                    let mirrored = moves
+                        .iter()
+                        .rposition(|n| n.mv == SgfMove::Swap(SwapKind::Pieces))
+                        .map_or(0, |idx| idx + 1);
+                    let placed = moves[mirrored..]
+                        .iter()
+                        .rposition(|n| n.mv == SgfMove::Tile(t))
+                        .map(|idx| idx + mirrored);
+                    match placed {
+                        Some(idx) => {
+                            moves.remove(idx);
+                        }
+                        None if mirrored > 0 && entry.previous() != PieceState::Empty => {
+                            return Err(Error::MirroredRemoval(t));
+                        }
+                        None => {}
+                    }
+                    match piece {
+                        PieceState::Colour(c) => (c, SgfMove::Tile(t)),
+                        PieceState::Empty => continue,
+                    }
+                }
+                (Move::Swap, _) => (
+                    entry.to_play(),
+                    SgfMove::Swap(entry.swap_kind().unwrap_or_default()),
+                ),
+            };
+            moves.push(Node {
+                colour,
+                mv,
+                comment: None,
+            });
+        }
+        let result = state.check_win().map(|c| match c {
+            Colour::Black => String::from("B+"),
+            Colour::White => String::from("W+"),
+        });
+        Ok(Self {
+            size: state.get_board().size,
+            black: None,
+            white: None,
+            result,
+            comment: None,
+            moves,
+        })
+    }
+
+    /// Plays the record on a new board. Resignations are skipped.
+    ///
+    /// # Errors
+    /// Will return an `Err` if a move could not be played
+    pub fn to_state(&self) -> Result<State, Error> {
+        let mut state = State::new(self.size);
+        for (idx, node) in self.moves.iter().enumerate() {
+            match node.mv {
+                SgfMove::Tile(t) => state.try_place_piece(t, PieceState::Colour(node.colour)),
+                SgfMove::Swap(kind) => {
+                    state.set_swap_kind(kind);
+                    state.swap()
+                }
+                SgfMove::Resign => Ok(()),
+            }
+            .map_err(|e| Error::IllegalMove(idx + 1, e))?;
+        }
+        Ok(state)
+    }
+}
+
+fn write_value(f: &mut core::fmt::Formatter<'_>, id: &str, value: &str) -> core::fmt::Result {
+    f.write_str(id)?;
+    f.write_char('[')?;
+    for c in value.chars() {
+        if matches!(c, ']' | '\\') {
+            f.write_char('\\')?;
+        }
+        f.write_char(c)?;
+    }
+    f.write_char(']')
+}
+
+impl core::fmt::Display for SgfMove {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Tile(t) => write!(f, "{}", Cell(*t)),
+            Self::Swap(SwapKind::Sides) => write!(f, "swap-sides"),
+            Self::Swap(SwapKind::Pieces) => write!(f, "swap-pieces"),
+            Self::Resign => write!(f, "resign"),
+        }
+    }
+}
+
+impl core::fmt::Display for Sgf {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "(;FF[4]GM[{GAME}]SZ[{}]", self.size)?;
+        let info = [
+            ("PB", &self.black),
+            ("PW", &self.white),
+            ("RE", &self.result),
+            ("C", &self.comment),
+        ];
+        for (id, value) in info {
+            if let Some(value) = value {
+                write_value(f, id, value)?;
+            }
+        }
+        for node in &self.moves {
+            let id = match node.colour {
+                Colour::Black => "B",
+                Colour::White => "W",
+            };
+            f.write_char(';')?;
+            write_value(f, id, &node.mv.to_string())?;
+            if let Some(comment) = &node.comment {
+                write_value(f, "C", comment)?;
+            }
+        }
+        writeln!(f, ")")
+    }
+}
+
+/// Reads the nodes of the main line, as lists of properties
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+type Property = (String, Vec<String>);
+
+impl Parser<'_> {
+    fn peek(&mut self) -> Option<char> {
+        self.input[self.pos..]
+            .char_indices()
+            .find(|(_, c)| !c.is_whitespace())
+            .map(|(i, c)| {
+                self.pos += i;
+                c
+            })
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), Error> {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            Ok(())
+        } else {
+            Err(Error::Syntax(self.pos))
+        }
+    }
+
+    fn value(&mut self) -> Result<String, Error> {
+        self.expect('[')?;
+        let mut value = String::new();
+        let mut chars = self.input[self.pos..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                ']' => {
+                    self.pos += i + 1;
+                    return Ok(value);
+                }
+                '\\' => value.extend(chars.next().map(|(_, c)| c)),
+                c => value.push(c),
+            }
+        }
+        Err(Error::Syntax(self.input.len()))
+    }
+
+    fn property(&mut self) -> Result<Property, Error> {
+        let start = self.pos;
+        let len = self.input[start..]
+            .find(|c: char| !c.is_ascii_uppercase())
+            .unwrap_or(self.input.len() - start);
+        if len == 0 {
+            return Err(Error::Syntax(start));
+        }
+        self.pos += len;
+        let mut values = vec![self.value()?];
+        while self.peek() == Some('[') {
+            values.push(self.value()?);
+        }
+        Ok((self.input[start..start + len].to_string(), values))
+    }
+
+    /// Nodes up to the end of the first variation at every branch
+    fn main_line(&mut self) -> Result<Vec<Vec<Property>>, Error> {
+        self.expect('(')?;
+        let mut nodes = Vec::new();
+        loop {
+            match self.peek() {
+                Some(';') => {
+                    self.pos += 1;
+                    let mut node = Vec::new();
+                    while self.peek().is_some_and(|c| c.is_ascii_uppercase()) {
+                        node.push(self.property()?);
+                    }
+                    nodes.push(node);
+                }
+                // Later variations are skipped
+                Some('(') => self.pos += 1,
+                Some(')') => return Ok(nodes),
+                _ => return Err(Error::Syntax(self.pos)),
+            }
+        }
+    }
+}
+
+fn parse_size(s: &str) -> Option<i8> {
+    let (cols, rows) = s.split_once(':').unwrap_or((s, s));
+    let size = cols.trim().parse::<i8>().ok()?;
+    (rows.trim().parse::<i8>().ok()? == size && (1..=MAX_SIZE).contains(&size)).then_some(size)
+}
+
+fn parse_move(s: &str, size: i8) -> Option<SgfMove> {
+    match s.trim() {
+        "swap-sides" | "swap" => Some(SgfMove::Swap(SwapKind::Sides)),
+        "swap-pieces" => Some(SgfMove::Swap(SwapKind::Pieces)),
+        "resign" => Some(SgfMove::Resign),
+        s => parse_cell(s, size).map(SgfMove::Tile),
+    }
+}
+
+impl FromStr for Sgf {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut nodes = Parser { input: s, pos: 0 }.main_line()?.into_iter();
+        let root = nodes.next().ok_or(Error::Syntax(0))?;
+        let mut sgf = Self {
+            size: 0,
+            black: None,
+            white: None,
+            result: None,
+            comment: None,
+            moves: Vec::new(),
+        };
+        for (id, mut values) in root {
+            let value = values.swap_remove(0);
+            match id.as_str() {
+                "GM" if value.trim() != GAME => return Err(Error::NotHex),
+                "SZ" => sgf.size = parse_size(&value).ok_or(Error::InvalidSize)?,
+                "PB" => sgf.black = Some(value),
+                "PW" => sgf.white = Some(value),
+                "RE" => sgf.result = Some(value),
+                "C" => sgf.comment = Some(value),
+                _ => {}
+            }
+        }
+        if sgf.size == 0 {
+            return Err(Error::InvalidSize);
+        }
+
+        for node in nodes {
+            let mut mv = None;
+            let mut comment = None;
+            for (id, mut values) in node {
+                let value = values.swap_remove(0);
+                match id.as_str() {
+                    "B" => mv = Some((Colour::Black, value)),
+                    "W" => mv = Some((Colour::White, value)),
+                    "C" => comment = Some(value),
+                    _ => {}
+                }
+            }
+            // Nodes without a move only carry setup or annotations
+            let Some((colour, value)) = mv else {
+                continue;
+            };
+            let number = sgf.moves.len() + 1;
+            sgf.moves.push(Node {
+                colour,
+                mv: parse_move(&value, sgf.size).ok_or(Error::InvalidMove(number))?,
+                comment,
+            });
+        }
+        Ok(sgf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let input = "(;FF[4]GM[11]SZ[3]PB[pincerhex]RE[B+]C[a \\] comment];B[b1];W[swap-pieces]\
+                     ;B[c1]C[good];W[b2](;B[c2])(;B[a3]))";
+        let sgf = input.parse::<Sgf>().unwrap();
+        assert_eq!(sgf.black.as_deref(), Some("pincerhex"));
+        assert_eq!(sgf.comment.as_deref(), Some("a ] comment"));
+        assert_eq!(sgf.moves.len(), 5);
+        assert_eq!(sgf.moves[1].mv, SgfMove::Swap(SwapKind::Pieces));
+        assert_eq!(sgf.moves[2].comment.as_deref(), Some("good"));
+
+        let state = sgf.to_state().unwrap();
+        assert_eq!(state.get_compressed(), "..B|WWB|...|");
+        assert_eq!(state.check_win(), None);
+        let mut exported = Sgf::from_state(&state).unwrap();
+        assert_eq!(
+            exported.to_string(),
+            "(;FF[4]GM[11]SZ[3];B[b1];W[swap-pieces];B[c1];W[b2];B[c2])\n"
+        );
+        exported.moves[2].comment = Some(String::from("good"));
+        exported.black = sgf.black.clone();
+        exported.result = sgf.result.clone();
+        exported.comment = sgf.comment;
+        assert_eq!(exported.to_string().parse::<Sgf>(), Ok(exported));
+    }
+
+    #[test]
+    fn removed_stones() {
+        let black = PieceState::Colour(Colour::Black);
+        let white = PieceState::Colour(Colour::White);
+        let mut state = State::new(3);
+        state.place_piece(Tile::Regular(0, 1), black).unwrap();
+        state.place_piece(Tile::Regular(1, 1), white).unwrap();
+        state
+            .place_piece(Tile::Regular(0, 1), PieceState::Empty)
+            .unwrap();
+        state.place_piece(Tile::Regular(1, 1), black).unwrap();
+        state.place_piece(Tile::Regular(0, 1), white).unwrap();
+
+        let record = Sgf::from_state(&state).unwrap().to_string();
+        assert_eq!(record, "(;FF[4]GM[11]SZ[3];B[b2];W[b1])\n");
+        let loaded = record.parse::<Sgf>().and_then(|sgf| sgf.to_state());
+        assert_eq!(
+            loaded.map(|s| s.get_compressed()),
+            Ok(state.get_compressed())
+        );
+
+        let mut state = State::new(3);
+        state.set_swap_kind(SwapKind::Pieces);
+        state.place_piece(Tile::Regular(0, 1), black).unwrap();
+        state.swap().unwrap();
+        state
+            .place_piece(Tile::Regular(1, 0), PieceState::Empty)
+            .unwrap();
+        assert_eq!(
+            Sgf::from_state(&state),
+            Err(Error::MirroredRemoval(Tile::Regular(1, 0)))
+        );
+    }
+}
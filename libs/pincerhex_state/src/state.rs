@@ -1,20 +1,92 @@
-use alloc::string::String;
-use pincerhex_core::{should_swap, Board, BoardError, Colour, PieceState, Rand, Tile};
+use alloc::{string::String, vec::Vec};
+use pincerhex_core::{
+    should_swap, zobrist, Bitboard, Board, BoardError, Colour, Move, PieceState, Rand, Tile,
+};
 
-use crate::{union_find::UnionFind, Winner};
+use crate::{
+    union_find::{Snapshot, UnionFind},
+    Winner,
+};
 
+#[derive(Clone)]
 pub struct State {
     size: i8,
     board: Board,
+    /// Copy of `board` for flood fills in playouts
+    bits: Bitboard,
     to_play: Colour,
     groups: Groups,
+    swap_rule: bool,
+    swap_kind: SwapKind,
+    /// Reject moves that break the rules instead of applying them
+    strict: bool,
+    history: Vec<HistoryEntry>,
+    /// Undone moves, most recently undone last
+    undone: Vec<HistoryEntry>,
+    /// Stones on the board the state was made from, which are not in the history
+    loaded: u16,
 }
 
-#[derive(Debug)]
+/// A move in the game's history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub mv: Move,
+    /// What a `Move::Move` put on its tile, `Empty` if it cleared the tile
+    pub piece: PieceState,
+    /// What was on the tile before
+    previous: PieceState,
+    /// Colour to play before the move
+    to_play: Colour,
+    /// How a `Move::Swap` was played
+    swap_kind: Option<SwapKind>,
+}
+
+impl HistoryEntry {
+    /// Colour that was to play when the move was made
+    #[must_use]
+    pub const fn to_play(&self) -> Colour {
+        self.to_play
+    }
+
+    /// What was on the tile before a `Move::Move`
+    #[must_use]
+    pub const fn previous(&self) -> PieceState {
+        self.previous
+    }
+
+    /// How the swap was played, `None` for regular moves
+    #[must_use]
+    pub const fn swap_kind(&self) -> Option<SwapKind> {
+        self.swap_kind
+    }
+}
+
+/// The two ways of playing the swap rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SwapKind {
+    /// The players switch colours. The board stays the same and the first player, now white,
+    /// is to play.
+    #[default]
+    Sides,
+    /// The opening stone is mirrored along the long diagonal and changes colour. The players
+    /// keep their colours, so black is to play again.
+    Pieces,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error {
     TileNotEmpty,
     InvalidTile,
     Board(BoardError),
+    /// The other colour is to play. Only returned in strict mode.
+    OutOfTurn(Colour),
+    /// A colour already connected its edges. Only returned in strict mode.
+    GameOver,
+    /// Swap without the swap rule, or anywhere but the second move. Only returned in strict
+    /// mode.
+    IllegalSwap,
+    /// Clearing a tile is not a move. Only returned in strict mode.
+    EmptyPiece,
 }
 
 impl core::fmt::Display for Error {
@@ -22,9 +94,11 @@ impl core::fmt::Display for Error {
         match self {
             Self::TileNotEmpty => write!(f, "tile not empty"),
             Self::InvalidTile => write!(f, "invalid tile"),
-            Self::Board(b) => match b {
-                BoardError::NotInRange => write!(f, "not in range"),
-            },
+            Self::OutOfTurn(c) => write!(f, "{c} is to play"),
+            Self::GameOver => write!(f, "game is over"),
+            Self::IllegalSwap => write!(f, "illegal swap"),
+            Self::EmptyPiece => write!(f, "cannot clear a tile"),
+            Self::Board(b) => write!(f, "{b}"),
         }
     }
 }
@@ -37,16 +111,85 @@ impl From<BoardError> for Error {
     }
 }
 
+impl From<Board> for State {
+    /// # Panics
+    /// Panics if the board is larger than `MAX_SIZE`, which `Board::try_from` rejects
+    fn from(board: Board) -> Self {
+        let mut state = Self {
+            size: board.size,
+            groups: Groups::new(board.size),
+            ..Default::default()
+        };
+        for (tile, s) in board.iter() {
+            if let PieceState::Colour(c) = s {
+                state.groups.join(tile, c, &board);
+            }
+        }
+        state.bits = Bitboard::from(&board);
+        state.loaded =
+            u16::try_from(state.groups.1.iter().map(Vec::len).sum::<usize>()).unwrap_or(u16::MAX);
+        state.board = board;
+        state
+    }
+}
+
 impl State {
+    /// # Panics
+    /// Panics if `size` is larger than `MAX_SIZE`
     #[must_use]
     pub fn new(size: i8) -> Self {
         Self {
             size,
             board: Board::new(size),
+            bits: Bitboard::new(size),
+            groups: Groups::new(size),
             ..Default::default()
         }
     }
 
+    /// # Panics
+    /// Panics if `size` is larger than `MAX_SIZE`
+    #[must_use]
+    pub fn with_swap_rule(size: i8, swap_rule: bool) -> Self {
+        Self {
+            swap_rule,
+            ..Self::new(size)
+        }
+    }
+
+    /// Whether the second player may swap after the first move
+    #[must_use]
+    pub const fn swap_rule(&self) -> bool {
+        self.swap_rule
+    }
+
+    pub const fn set_swap_rule(&mut self, swap_rule: bool) {
+        self.swap_rule = swap_rule;
+    }
+
+    /// How `swap` is played
+    #[must_use]
+    pub const fn swap_kind(&self) -> SwapKind {
+        self.swap_kind
+    }
+
+    pub const fn set_swap_kind(&mut self, swap_kind: SwapKind) {
+        self.swap_kind = swap_kind;
+    }
+
+    /// Whether moves that break the rules are rejected
+    #[must_use]
+    pub const fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// In strict mode moves must alternate, go on empty tiles and come before the game is won,
+    /// and swaps must follow the swap rule. Otherwise any change to the board is accepted.
+    pub const fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Colour to play next, taking swaps into account
     #[must_use]
     pub const fn active(&self) -> Colour {
         self.to_play
@@ -61,7 +204,24 @@ impl State {
         &self.board
     }
 
-    pub fn should_swap(&self, rng: &mut impl Rand) -> bool {
+    #[must_use]
+    pub const fn bitboard(&self) -> &Bitboard {
+        &self.bits
+    }
+
+    /// Zobrist hash of the board and the colour to play
+    #[must_use]
+    pub const fn zobrist(&self) -> u64 {
+        match self.to_play {
+            Colour::Black => self.board.zobrist(),
+            Colour::White => self.board.zobrist() ^ zobrist::WHITE_TO_PLAY,
+        }
+    }
+
+    pub fn should_swap(&self, rng: &mut (impl Rand + ?Sized)) -> bool {
+        if !self.swap_rule {
+            return false;
+        }
         for i in self.board.iter() {
             if let (Tile::Regular(r, c), PieceState::Colour(_)) = i {
                 return should_swap(r, c, self.size, rng);
@@ -70,11 +230,21 @@ impl State {
         false
     }
 
-    /// # Errors
-    /// Will return an `Err` if a set tile was invalid
-    #[allow(dead_code)]
-    pub fn swap_pieces(&mut self) -> Result<(), Error> {
+    /// Mirrors the board and joins the mirrored stones again, without looking at empty tiles
+    fn swap_pieces(&mut self) -> Result<(), Error> {
         self.board.swap_pieces()?;
+        let stones = self.groups.clear();
+        for &(tile, _) in &stones {
+            self.bits.set_tile(tile, PieceState::Empty)?;
+        }
+        for (tile, c) in stones {
+            if let Tile::Regular(row, col) = tile {
+                let mirrored = Tile::Regular(col, row);
+                self.bits
+                    .set_tile(mirrored, PieceState::Colour(c.opponent()))?;
+                self.groups.join(mirrored, c.opponent(), &self.board);
+            }
+        }
         Ok(())
     }
 
@@ -88,13 +258,12 @@ impl State {
         format!("{}", self.board)
     }
 
-    fn is_connected(&mut self, colour: Colour) -> bool {
-        self.groups
-            .get_mut(colour)
-            .connected(Tile::Edge1, Tile::Edge2)
+    fn is_connected(&self, colour: Colour) -> bool {
+        self.groups.get(colour).connected(Tile::Edge1, Tile::Edge2)
     }
 
-    pub fn get_winner(&mut self, colour: Colour) -> Option<Winner> {
+    #[must_use]
+    pub fn get_winner(&self, colour: Colour) -> Option<Winner> {
         self.check_win().map(|w| {
             if w == colour {
                 Some(Winner::Bot)
@@ -104,7 +273,17 @@ impl State {
         })?
     }
 
-    pub fn check_win(&mut self) -> Option<Colour> {
+    /// Winner as given by [`Self::check_win`] and the shortest chain of their stones connecting
+    /// their edges, starting from their first edge
+    #[must_use]
+    pub fn winning_path(&self) -> Option<(Colour, Vec<Tile>)> {
+        let winner = self.check_win()?;
+        Some((winner, self.bits.shortest_path(winner)?))
+    }
+
+    /// Checks White first, in the same order as [`Bitboard::winner`]
+    #[must_use]
+    pub fn check_win(&self) -> Option<Colour> {
         if self.is_connected(Colour::White) {
             Some(Colour::White)
         } else if self.is_connected(Colour::Black) {
@@ -116,6 +295,7 @@ impl State {
 
     fn set_piece(&mut self, t: Tile, s: PieceState) -> Result<(), Error> {
         self.board.set_tile(t, s)?;
+        self.bits.set_tile(t, s)?;
         if let PieceState::Colour(c) = s {
             self.groups.join(t, c, &self.board);
             self.to_play = c.opponent();
@@ -123,42 +303,187 @@ impl State {
         Ok(())
     }
 
-    fn replace_piece(&mut self, t: Tile, old: Colour, new: PieceState) -> Result<(), Error> {
-        self.board.set_tile(t, new)?;
-        self.groups.0[old.group_idx()] = UnionFind::new(self.size.try_into().unwrap());
-        for i in self.board.iter() {
-            if matches!(i.1, PieceState::Colour(c) if c == old) {
-                self.groups.join(i.0, old, &self.board);
+    /// Takes the stone of colour `old` off `t`, only rejoining the stones placed after it
+    fn remove_piece(&mut self, t: Tile, old: Colour) -> Result<(), Error> {
+        self.board.set_tile(t, PieceState::Empty)?;
+        self.bits.set_tile(t, PieceState::Empty)?;
+        self.groups.remove(t, old, &self.board);
+        Ok(())
+    }
+
+    fn apply(&mut self, t: Tile, s: PieceState) -> Result<(), Error> {
+        match self.board.get_tile(t).ok_or(Error::InvalidTile)? {
+            PieceState::Colour(c) if s == PieceState::Colour(c) => {
+                self.to_play = c.opponent();
+                return Ok(());
             }
+            PieceState::Colour(c) => self.remove_piece(t, c)?,
+            PieceState::Empty => {}
         }
+        self.set_piece(t, s)?;
         Ok(())
     }
 
+    fn record(&mut self, mv: Move, piece: PieceState, previous: PieceState, to_play: Colour) {
+        self.history.push(HistoryEntry {
+            mv,
+            piece,
+            previous,
+            to_play,
+            swap_kind: None,
+        });
+        self.undone.clear();
+    }
+
+    /// Plays a swap of the given kind on the board, starting from `to_play`
+    fn apply_swap(&mut self, kind: SwapKind, to_play: Colour) -> Result<(), Error> {
+        match kind {
+            SwapKind::Sides => self.to_play = to_play,
+            SwapKind::Pieces => {
+                self.swap_pieces()?;
+                self.to_play = to_play.opponent();
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks a move against the rules in strict mode
+    fn check_move(&self, previous: PieceState, s: PieceState) -> Result<(), Error> {
+        if !self.strict {
+            return Ok(());
+        }
+        let PieceState::Colour(colour) = s else {
+            return Err(Error::EmptyPiece);
+        };
+        if self.check_win().is_some() {
+            Err(Error::GameOver)
+        } else if colour != self.to_play {
+            Err(Error::OutOfTurn(self.to_play))
+        } else if previous != PieceState::Empty {
+            Err(Error::TileNotEmpty)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Clearing a tile that is already empty changes nothing and is not recorded.
+    ///
     /// # Errors
-    /// Will return an `Err` if given an invalid tile
+    /// Will return an `Err` if given an invalid tile or a tile that already has a stone of that
+    /// colour, or in strict mode if the move breaks the rules
     pub fn place_piece(&mut self, t: Tile, s: PieceState) -> Result<(), Error> {
-        if let PieceState::Colour(c) = self.board.get_tile(t).ok_or(Error::InvalidTile)? {
-            match (s, c) {
-                (PieceState::Empty, c) => self.replace_piece(t, c, s),
-                (PieceState::Colour(to_place), c) if to_place != c => self.replace_piece(t, c, s),
-                (_, _) => Ok(()),
-            }?;
+        let previous = self.board.get_tile(t).ok_or(Error::InvalidTile)?;
+        self.check_move(previous, s)?;
+        if previous == s {
+            return match s {
+                PieceState::Empty => Ok(()),
+                PieceState::Colour(_) => Err(Error::TileNotEmpty),
+            };
         }
-        self.set_piece(t, s)?;
+        let to_play = self.to_play;
+        self.apply(t, s)?;
+        self.record(Move::Move(t), s, previous, to_play);
         Ok(())
     }
 
     /// # Errors
-    /// Will return an `Err` if the tile is not empty
+    /// Will return an `Err` if the tile is not empty, or in strict mode if the move breaks the
+    /// rules
     #[allow(dead_code)]
     pub fn try_place_piece(&mut self, t: Tile, c: PieceState) -> Result<(), Error> {
         if self.board.get_tile(t) == Some(PieceState::Empty) {
+            self.check_move(PieceState::Empty, c)?;
+            let to_play = self.to_play;
             self.set_piece(t, c)?;
+            self.record(Move::Move(t), c, PieceState::Empty, to_play);
             Ok(())
         } else {
             Err(Error::TileNotEmpty)
         }
     }
+
+    /// Plays the swap rule as set by `set_swap_kind` and records it in the history.
+    ///
+    /// # Errors
+    /// In strict mode, will return an `Err` unless the swap rule is on and exactly one stone was
+    /// played, or if a set tile was invalid
+    pub fn swap(&mut self) -> Result<(), Error> {
+        let legal = self.swap_rule
+            && matches!(
+                self.history.as_slice(),
+                [HistoryEntry {
+                    mv: Move::Move(_),
+                    piece: PieceState::Colour(_),
+                    ..
+                }]
+            );
+        if self.strict && !legal {
+            return Err(Error::IllegalSwap);
+        }
+        let (kind, to_play) = (self.swap_kind, self.to_play);
+        self.apply_swap(kind, to_play)?;
+        self.history.push(HistoryEntry {
+            mv: Move::Swap,
+            piece: PieceState::Empty,
+            previous: PieceState::Empty,
+            to_play,
+            swap_kind: Some(kind),
+        });
+        self.undone.clear();
+        Ok(())
+    }
+
+    /// Moves played so far, oldest first
+    #[must_use]
+    pub fn history(&self) -> &[HistoryEntry] {
+        &self.history
+    }
+
+    /// Number of stones placed so far by both players, counting the stones of the board the
+    /// state was made from. Swaps and removals don't count.
+    #[must_use]
+    pub fn move_count(&self) -> u16 {
+        let placed = self
+            .history
+            .iter()
+            .filter(|e| matches!(e.mv, Move::Move(_)) && e.piece != PieceState::Empty)
+            .count();
+        u16::try_from(placed)
+            .unwrap_or(u16::MAX)
+            .saturating_add(self.loaded)
+    }
+
+    /// Takes back the last move. Returns `None` if there is nothing to undo.
+    ///
+    /// # Panics
+    /// Panics if a recorded tile is not on the board, which cannot happen
+    pub fn undo(&mut self) -> Option<HistoryEntry> {
+        let entry = self.history.pop()?;
+        match entry.mv {
+            Move::Move(t) => self.apply(t, entry.previous),
+            // Mirroring twice restores the board
+            Move::Swap => self.apply_swap(entry.swap_kind.unwrap_or_default(), entry.to_play),
+        }
+        .expect("tiles in the history are valid");
+        self.to_play = entry.to_play;
+        self.undone.push(entry);
+        Some(entry)
+    }
+
+    /// Plays the last undone move again. Returns `None` if there is nothing to redo.
+    ///
+    /// # Panics
+    /// Panics if a recorded tile is not on the board, which cannot happen
+    pub fn redo(&mut self) -> Option<HistoryEntry> {
+        let entry = self.undone.pop()?;
+        match entry.mv {
+            Move::Move(t) => self.apply(t, entry.piece),
+            Move::Swap => self.apply_swap(entry.swap_kind.unwrap_or_default(), entry.to_play),
+        }
+        .expect("tiles in the history are valid");
+        self.history.push(entry);
+        Some(entry)
+    }
 }
 
 impl Default for State {
@@ -166,34 +491,402 @@ impl Default for State {
         Self {
             size: DEFAULT_SIZE,
             board: Board::new(DEFAULT_SIZE),
+            bits: Bitboard::new(DEFAULT_SIZE),
             to_play: Colour::Black,
-            groups: Groups([UnionFind::new(0), UnionFind::new(0)]),
+            groups: Groups::new(DEFAULT_SIZE),
+            swap_rule: true,
+            swap_kind: SwapKind::Sides,
+            strict: false,
+            history: Vec::new(),
+            undone: Vec::new(),
+            loaded: 0,
         }
     }
 }
 
-struct Groups([UnionFind; 2]);
+#[derive(Clone)]
+struct Groups(
+    [UnionFind; 2],
+    /// Stones of each colour in the order they were joined, with the union-find snapshot from
+    /// before they were
+    [Vec<(Tile, Snapshot)>; 2],
+    /// Whether each tile holds a joined stone of each colour, by tile index. Stones only join
+    /// the stones joined before them, so going back to before one also leaves it out of the
+    /// unions of the others.
+    [Vec<bool>; 2],
+);
 
 impl Groups {
-    pub fn get_mut(&mut self, c: Colour) -> &mut UnionFind {
-        &mut self.0[c.group_idx()]
+    fn new(size: i8) -> Self {
+        let tiles = (size.max(0) as usize).pow(2);
+        Self(
+            [UnionFind::new(size), UnionFind::new(size)],
+            [Vec::new(), Vec::new()],
+            [vec![false; tiles], vec![false; tiles]],
+        )
+    }
+
+    pub const fn get(&self, c: Colour) -> &UnionFind {
+        &self.0[c.group_idx()]
     }
 
     pub fn join(&mut self, t: Tile, c: Colour, board: &Board) {
+        let (set, joined) = (&mut self.0[c.group_idx()], &mut self.2[c.group_idx()]);
+        let Some(idx) = t.to_index(board.size) else {
+            return;
+        };
+        self.1[c.group_idx()].push((t, set.snapshot()));
+        joined[idx] = true;
         if t.edge(c) == 0 {
-            self.0[c.group_idx()].union(Tile::Edge1, t);
+            set.union(Tile::Edge1, t);
         }
         if t.edge(c) == board.size - 1 {
-            self.0[c.group_idx()].union(t, Tile::Edge2);
+            set.union(t, Tile::Edge2);
+        }
+
+        for (neighbour, _) in board.neighbours(t).into_iter().flatten() {
+            if neighbour.to_index(board.size).is_some_and(|n| joined[n]) {
+                set.union(neighbour, t);
+            }
         }
+    }
 
-        for n in board.neighbours(t) {
-            match n {
-                Some((neighbour, PieceState::Colour(n_colour))) if n_colour == c => {
-                    self.0[c.group_idx()].union(neighbour, t);
-                }
-                Some(_) | None => {}
+    /// Rolls the group of `t` back to before `t` was joined and joins the later stones of that
+    /// group again, so taking back the last stone only undoes its own unions. The stones joined
+    /// again move to the end, which is the order their unions are in now. `board` must no
+    /// longer have the stone.
+    pub fn remove(&mut self, t: Tile, c: Colour, board: &Board) {
+        let (set, stones) = (&mut self.0[c.group_idx()], &mut self.1[c.group_idx()]);
+        let Some(pos) = stones.iter().rposition(|&(s, _)| s == t) else {
+            return;
+        };
+        let (mut kept, affected): (Vec<_>, Vec<_>) = stones
+            .split_off(pos + 1)
+            .into_iter()
+            .partition(|&(s, _)| !set.connected(s, t));
+        if let Some((_, snapshot)) = stones.pop() {
+            set.restore_set(snapshot, t, kept.iter_mut().map(|(_, s)| s));
+        }
+        stones.append(&mut kept);
+
+        let joined = &mut self.2[c.group_idx()];
+        for tile in affected.iter().map(|&(s, _)| s).chain([t]) {
+            if let Some(idx) = tile.to_index(board.size) {
+                joined[idx] = false;
             }
         }
+        for (tile, _) in affected {
+            self.join(tile, c, board);
+        }
+    }
+
+    /// Empties both colours' groups, returning their stones
+    pub fn clear(&mut self) -> Vec<(Tile, Colour)> {
+        let mut stones = Vec::new();
+        for c in [Colour::Black, Colour::White] {
+            self.0[c.group_idx()].restore(Snapshot::default());
+            self.2[c.group_idx()].fill(false);
+            stones.extend(self.1[c.group_idx()].drain(..).map(|(t, _)| (t, c)));
+        }
+        stones
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn undo_and_redo() {
+        let black = PieceState::Colour(Colour::Black);
+        let white = PieceState::Colour(Colour::White);
+        let mut state = State::new(3);
+        state.place_piece(Tile::Regular(0, 0), black).unwrap();
+        state.swap().unwrap();
+        state.place_piece(Tile::Regular(1, 0), white).unwrap();
+        state.place_piece(Tile::Regular(1, 0), black).unwrap();
+        state.place_piece(Tile::Regular(2, 0), black).unwrap();
+        assert_eq!(state.check_win(), Some(Colour::Black));
+        assert_eq!(state.history().len(), 5);
+
+        state.undo();
+        state.undo();
+        assert_eq!(state.check_win(), None);
+        assert_eq!(state.get_board().get_tile(Tile::Regular(1, 0)), Some(white));
+        assert_eq!(state.active(), Colour::Black);
+
+        assert_eq!(state.redo().map(|e| e.piece), Some(black));
+        assert_eq!(state.get_board().get_tile(Tile::Regular(1, 0)), Some(black));
+
+        while state.undo().is_some() {}
+        assert_eq!(state.get_compressed(), "...|...|...|");
+        assert_eq!(state.active(), Colour::Black);
+
+        state.redo();
+        state.place_piece(Tile::Regular(2, 2), white).unwrap();
+        assert!(state.redo().is_none());
+        assert_eq!(state.history().len(), 2);
+    }
+
+    #[test]
+    fn undo_past_swap() {
+        let black = PieceState::Colour(Colour::Black);
+        let white = PieceState::Colour(Colour::White);
+        let mut state = State::new(3);
+        state.set_strict(true);
+        state.place_piece(Tile::Regular(0, 1), black).unwrap();
+        let before = (state.get_compressed(), state.zobrist(), state.active());
+        state.swap().unwrap();
+        state.place_piece(Tile::Regular(1, 1), white).unwrap();
+
+        state.undo();
+        state.undo();
+        assert_eq!(
+            (state.get_compressed(), state.zobrist(), state.active()),
+            before
+        );
+        // The swap is legal again, and playing it drops the undone moves
+        state.swap().unwrap();
+        assert!(state.redo().is_none());
+
+        state.place_piece(Tile::Regular(2, 2), white).unwrap();
+        state.undo();
+        state.place_piece(Tile::Regular(2, 0), white).unwrap();
+        assert!(state.redo().is_none());
+        assert_eq!(state.history().len(), 3);
+        assert_eq!(
+            state.get_board().get_tile(Tile::Regular(2, 2)),
+            Some(PieceState::Empty)
+        );
+    }
+
+    #[test]
+    fn zobrist() {
+        let black = PieceState::Colour(Colour::Black);
+        let white = PieceState::Colour(Colour::White);
+        let mut state = State::new(4);
+        let empty = state.zobrist();
+        state.place_piece(Tile::Regular(1, 1), black).unwrap();
+        state.place_piece(Tile::Regular(2, 2), white).unwrap();
+        let two = state.zobrist();
+        state.place_piece(Tile::Regular(0, 3), black).unwrap();
+        state.undo();
+        assert_eq!(state.zobrist(), two);
+        state.undo();
+        state.undo();
+        assert_eq!(state.zobrist(), empty);
+
+        // The same stones reached in another order
+        let mut transposed = State::new(4);
+        transposed.place_piece(Tile::Regular(0, 3), black).unwrap();
+        transposed.place_piece(Tile::Regular(2, 2), white).unwrap();
+        transposed.place_piece(Tile::Regular(1, 1), black).unwrap();
+        state.redo();
+        state.redo();
+        state.redo();
+        assert_eq!(transposed.zobrist(), state.zobrist());
+        assert_eq!(
+            State::from(state.get_board().clone()).zobrist(),
+            state.zobrist() ^ zobrist::WHITE_TO_PLAY
+        );
+
+        // Only the colour to play differs
+        transposed.set_to_play(Colour::Black);
+        assert_ne!(transposed.zobrist(), state.zobrist());
+    }
+
+    #[test]
+    fn remove_stones() {
+        let black = PieceState::Colour(Colour::Black);
+        let mut state = State::new(3);
+        for row in 0..3 {
+            state.place_piece(Tile::Regular(row, 1), black).unwrap();
+        }
+        state.place_piece(Tile::Regular(1, 2), black).unwrap();
+        assert_eq!(state.check_win(), Some(Colour::Black));
+
+        state
+            .place_piece(Tile::Regular(1, 1), PieceState::Empty)
+            .unwrap();
+        assert_eq!(state.check_win(), None);
+        state
+            .place_piece(Tile::Regular(2, 2), PieceState::Colour(Colour::White))
+            .unwrap();
+        state.place_piece(Tile::Regular(0, 2), black).unwrap();
+        assert_eq!(state.check_win(), Some(Colour::Black));
+        state
+            .place_piece(Tile::Regular(2, 1), PieceState::Empty)
+            .unwrap();
+        assert_eq!(state.check_win(), None);
+        state.place_piece(Tile::Regular(2, 1), black).unwrap();
+        assert_eq!(state.check_win(), Some(Colour::Black));
+        state.undo();
+        state.undo();
+        state.undo();
+        state.undo();
+        assert_eq!(state.get_compressed(), ".B.|..B|.B.|");
+        state.undo();
+        assert_eq!(state.check_win(), Some(Colour::Black));
+        assert_eq!(state.bitboard().winner(), Some(Colour::Black));
+    }
+
+    #[test]
+    fn remove_from_a_loaded_board() {
+        let mut state = State::from(Board::try_from("B.B|BB.|B..|").unwrap());
+        assert_eq!(state.check_win(), Some(Colour::Black));
+        for tile in [Tile::Regular(0, 0), Tile::Regular(1, 1)] {
+            state.place_piece(tile, PieceState::Empty).unwrap();
+        }
+        assert_eq!(state.check_win(), None);
+    }
+
+    #[test]
+    fn remove_from_the_middle() {
+        let black = PieceState::Colour(Colour::Black);
+        let mut state = State::new(4);
+        for tile in [(0, 0), (3, 3), (1, 0), (2, 3), (2, 0), (1, 3)] {
+            state
+                .place_piece(Tile::Regular(tile.0, tile.1), black)
+                .unwrap();
+        }
+        state
+            .place_piece(Tile::Regular(1, 0), PieceState::Empty)
+            .unwrap();
+        state.place_piece(Tile::Regular(0, 3), black).unwrap();
+        assert_eq!(state.check_win(), Some(Colour::Black));
+        state.undo();
+        assert_eq!(state.check_win(), None);
+        state.undo();
+        state.place_piece(Tile::Regular(3, 0), black).unwrap();
+        assert_eq!(state.check_win(), Some(Colour::Black));
+    }
+
+    #[test]
+    fn repeated_win_checks() {
+        let state = State::from(Board::try_from("B.W|BW.|B..|").unwrap());
+        let changes = state.groups.0.each_ref().map(UnionFind::snapshot);
+        for _ in 0..100 {
+            assert_eq!(state.check_win(), Some(Colour::Black));
+            assert_eq!(state.winning_path().map(|(c, _)| c), Some(Colour::Black));
+        }
+        assert_eq!(state.groups.0.each_ref().map(UnionFind::snapshot), changes);
+    }
+
+    #[test]
+    fn strict_mode() {
+        let black = PieceState::Colour(Colour::Black);
+        let white = PieceState::Colour(Colour::White);
+        let mut state = State::new(2);
+        state.set_strict(true);
+        assert_eq!(state.swap(), Err(Error::IllegalSwap));
+        assert_eq!(
+            state.place_piece(Tile::Regular(0, 0), white),
+            Err(Error::OutOfTurn(Colour::Black))
+        );
+        state.place_piece(Tile::Regular(0, 0), black).unwrap();
+        assert_eq!(
+            state.place_piece(Tile::Regular(0, 0), white),
+            Err(Error::TileNotEmpty)
+        );
+        assert_eq!(
+            state.place_piece(Tile::Regular(0, 0), PieceState::Empty),
+            Err(Error::EmptyPiece)
+        );
+        state.swap().unwrap();
+        assert_eq!(state.swap(), Err(Error::IllegalSwap));
+        state.place_piece(Tile::Regular(0, 1), white).unwrap();
+        state.try_place_piece(Tile::Regular(1, 0), black).unwrap();
+        assert_eq!(
+            state.place_piece(Tile::Regular(1, 1), white),
+            Err(Error::GameOver)
+        );
+    }
+
+    #[test]
+    fn loaded_move_count() {
+        let mut state = State::from(Board::try_from("B..|.W.|..B|").unwrap());
+        assert_eq!(state.move_count(), 3);
+        state
+            .place_piece(Tile::Regular(0, 1), PieceState::Colour(Colour::White))
+            .unwrap();
+        assert_eq!(state.move_count(), 4);
+    }
+
+    #[test]
+    fn no_op_moves() {
+        let black = PieceState::Colour(Colour::Black);
+        let mut state = State::new(3);
+        state.place_piece(Tile::Regular(0, 0), black).unwrap();
+        assert_eq!(
+            state.place_piece(Tile::Regular(0, 0), black),
+            Err(Error::TileNotEmpty)
+        );
+        state
+            .place_piece(Tile::Regular(1, 1), PieceState::Empty)
+            .unwrap();
+        assert_eq!(state.history().len(), 1);
+        assert_eq!(state.move_count(), 1);
+        assert_eq!(state.active(), Colour::White);
+        state.undo();
+        assert_eq!(state.get_compressed(), "...|...|...|");
+        assert!(state.undo().is_none());
+    }
+
+    #[test]
+    fn illegal_swaps() {
+        let black = PieceState::Colour(Colour::Black);
+        let white = PieceState::Colour(Colour::White);
+        let mut state = State::with_swap_rule(3, false);
+        state.set_strict(true);
+        state.place_piece(Tile::Regular(0, 1), black).unwrap();
+        assert_eq!(state.swap(), Err(Error::IllegalSwap));
+        assert_eq!(state.history().len(), 1);
+        assert_eq!(state.active(), Colour::White);
+
+        let mut state = State::new(3);
+        state.set_strict(true);
+        state.set_swap_kind(SwapKind::Pieces);
+        state.place_piece(Tile::Regular(0, 1), black).unwrap();
+        state.swap().unwrap();
+        let swapped = state.get_compressed();
+        assert_eq!(state.swap(), Err(Error::IllegalSwap));
+        assert_eq!(state.get_compressed(), swapped);
+        state.place_piece(Tile::Regular(2, 2), black).unwrap();
+        state.place_piece(Tile::Regular(0, 0), white).unwrap();
+        assert_eq!(state.swap(), Err(Error::IllegalSwap));
+
+        // Outside strict mode a second swap is played and can be taken back
+        let mut state = State::new(3);
+        state.set_swap_kind(SwapKind::Pieces);
+        state.place_piece(Tile::Regular(0, 1), black).unwrap();
+        state.swap().unwrap();
+        state.swap().unwrap();
+        assert_eq!(state.get_compressed(), ".B.|...|...|");
+        state.undo();
+        state.undo();
+        assert_eq!(state.get_compressed(), ".B.|...|...|");
+        assert_eq!(state.history().len(), 1);
+    }
+
+    #[test]
+    fn swap_pieces() {
+        let black = PieceState::Colour(Colour::Black);
+        let mut state = State::new(3);
+        state.set_swap_kind(SwapKind::Pieces);
+        state.place_piece(Tile::Regular(0, 1), black).unwrap();
+        state.swap().unwrap();
+        assert_eq!(state.get_compressed(), "...|W..|...|");
+        assert_eq!(state.active(), Colour::Black);
+        assert_eq!(state.history()[1].swap_kind(), Some(SwapKind::Pieces));
+
+        state.undo();
+        assert_eq!(state.get_compressed(), ".B.|...|...|");
+        assert_eq!(state.active(), Colour::White);
+        let before = state.zobrist();
+        state.redo();
+        assert_eq!(state.get_compressed(), "...|W..|...|");
+        assert_eq!(state.active(), Colour::Black);
+        state.undo();
+        assert_eq!(state.zobrist(), before);
     }
 }
@@ -1,65 +1,214 @@
-extern crate alloc;
+//! Disjoint sets of the cells of a board and its two edges.
+//!
+//! The sets are stored in a flat array indexed by tile index with the edges after the cells.
+//! Every change is logged so that it can be rolled back, which is how stones are taken off the
+//! board. Sets are joined by rank and `union` compresses the paths it walks. Compressing is a
+//! logged change like any other, so it is only done when joining: looking a set up never changes
+//! anything and the log only grows with the number of joins.
 
-use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
 use core::cmp::Ordering;
 use pincerhex_core::Tile;
 
 #[derive(Clone)]
 pub struct UnionFind {
-    set: BTreeMap<Tile, Element>,
+    size: i8,
+    set: Vec<Element>,
+    /// Previous value of every element written, oldest first
+    log: Vec<Change>,
 }
 
 #[derive(Copy, Clone)]
 struct Element {
-    parent: Tile,
+    parent: u16,
     rank: u16,
 }
 
+#[derive(Copy, Clone)]
+struct Change {
+    idx: u16,
+    old: Element,
+}
+
+/// Point in the changes of a [`UnionFind`], to go back to with [`UnionFind::restore`]. The
+/// default is before any change.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Snapshot(usize);
+
 impl UnionFind {
-    pub const fn new(_size: usize) -> Self {
+    /// Every cell of a board of the given size and both edges in their own set
+    #[must_use]
+    pub fn new(size: i8) -> Self {
+        let len = (size.max(0) as usize).pow(2) + 2;
         Self {
-            set: BTreeMap::new(),
+            size,
+            set: (0..len)
+                .map(|i| Element {
+                    parent: i as u16,
+                    rank: 0,
+                })
+                .collect(),
+            log: Vec::new(),
         }
     }
 
-    pub fn connected(&mut self, x: Tile, y: Tile) -> bool {
-        self.find(x) == self.find(y)
+    const fn index(&self, t: Tile) -> Option<usize> {
+        match t {
+            Tile::Edge1 => Some(self.set.len() - 2),
+            Tile::Edge2 => Some(self.set.len() - 1),
+            t => t.to_index(self.size),
+        }
     }
 
-    pub fn find(&mut self, x: Tile) -> Tile {
-        if let Some(px) = self.set.get(&x) {
-            if px.parent == x {
-                return x;
-            }
-            let gx = self.set[&px.parent];
-            if gx.parent == px.parent {
-                return px.parent;
-            }
-            self.set.get_mut(&x).unwrap().parent = gx.parent;
-            return self.find(gx.parent);
+    fn write(&mut self, idx: usize, element: Element) {
+        self.log.push(Change {
+            idx: idx as u16,
+            old: self.set[idx],
+        });
+        self.set[idx] = element;
+    }
+
+    #[must_use]
+    pub fn connected(&self, x: Tile, y: Tile) -> bool {
+        match (self.find(x), self.find(y)) {
+            (Some(x), Some(y)) => x == y,
+            _ => false,
+        }
+    }
+
+    /// Index of the representative of the set containing `x`, or `None` for tiles not on the
+    /// board
+    #[must_use]
+    pub fn find(&self, x: Tile) -> Option<usize> {
+        self.index(x).map(|x| self.root(x))
+    }
+
+    fn root(&self, mut x: usize) -> usize {
+        while self.set[x].parent as usize != x {
+            x = self.set[x].parent as usize;
         }
-        self.set.insert(x, Element { parent: x, rank: 0 });
         x
     }
 
+    /// Same as `root` but points the whole path at the root, logging every rewrite
+    fn compress(&mut self, x: usize) -> usize {
+        let root = self.root(x);
+        let mut x = x;
+        while x != root && self.set[x].parent as usize != root {
+            let next = self.set[x].parent as usize;
+            self.write(
+                x,
+                Element {
+                    parent: root as u16,
+                    ..self.set[x]
+                },
+            );
+            x = next;
+        }
+        root
+    }
+
+    /// Joins the sets of `x` and `y`. Tiles not on the board are ignored.
     pub fn union(&mut self, x: Tile, y: Tile) {
-        let rep_x = self.find(x);
-        let rep_y = self.find(y);
+        let (Some(x), Some(y)) = (self.index(x), self.index(y)) else {
+            return;
+        };
+        let (rep_x, rep_y) = (self.compress(x), self.compress(y));
         if rep_x == rep_y {
             return;
         }
-        let x = self.set.get(&rep_x).unwrap();
-        let y = self.set.get(&rep_y).unwrap();
-
+        let (x, y) = (self.set[rep_x], self.set[rep_y]);
         match x.rank.cmp(&y.rank) {
-            Ordering::Less => self.set.get_mut(&rep_x).unwrap().parent = y.parent,
-            Ordering::Greater => self.set.get_mut(&rep_y).unwrap().parent = x.parent,
+            Ordering::Less => self.write(
+                rep_x,
+                Element {
+                    parent: rep_y as u16,
+                    ..x
+                },
+            ),
+            Ordering::Greater => self.write(
+                rep_y,
+                Element {
+                    parent: rep_x as u16,
+                    ..y
+                },
+            ),
             Ordering::Equal => {
-                self.set.get_mut(&rep_x).unwrap().parent = y.parent;
-                self.set.get_mut(&rep_y).unwrap().rank += 1;
+                self.write(
+                    rep_x,
+                    Element {
+                        parent: rep_y as u16,
+                        ..x
+                    },
+                );
+                self.write(
+                    rep_y,
+                    Element {
+                        rank: y.rank + 1,
+                        ..y
+                    },
+                );
             }
         }
     }
+
+    /// Current point in the changes, only as large as a log length
+    #[must_use]
+    pub const fn snapshot(&self) -> Snapshot {
+        Snapshot(self.log.len())
+    }
+
+    /// Undoes every change made since `snapshot`, in time proportional to their number
+    ///
+    /// # Panics
+    /// Panics if `snapshot` is later than the current one
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        let Self { set, log, .. } = self;
+        for change in log.drain(snapshot.0..).rev() {
+            set[change.idx as usize] = change.old;
+        }
+    }
+
+    /// Undoes the changes made since `snapshot` to the set containing `x` only. Sets never
+    /// split, so the other sets never pointed into it and keep their changes. `later` are
+    /// snapshots taken since `snapshot`, which are moved to where the changes kept are now.
+    ///
+    /// # Panics
+    /// Panics if `snapshot` is later than the current one
+    pub fn restore_set<'a>(
+        &mut self,
+        snapshot: Snapshot,
+        x: Tile,
+        later: impl IntoIterator<Item = &'a mut Snapshot>,
+    ) {
+        let Some(root) = self.find(x) else {
+            return;
+        };
+        let changes = self.log.split_off(snapshot.0);
+        let undone = changes
+            .iter()
+            .map(|change| self.root(change.idx as usize) == root)
+            .collect::<Vec<_>>();
+        for (change, _) in changes.iter().zip(&undone).rev().filter(|(_, &u)| u) {
+            self.set[change.idx as usize] = change.old;
+        }
+
+        // Changes kept before each of the changes since `snapshot`
+        let mut kept = Vec::with_capacity(changes.len() + 1);
+        kept.push(0);
+        for &u in &undone {
+            kept.push(kept[kept.len() - 1] + usize::from(!u));
+        }
+        for s in later {
+            s.0 = snapshot.0 + kept[s.0 - snapshot.0];
+        }
+        self.log.extend(
+            changes
+                .into_iter()
+                .zip(undone)
+                .filter_map(|(change, u)| (!u).then_some(change)),
+        );
+    }
 }
 
 #[cfg(test)]
@@ -73,4 +222,59 @@ mod test {
         uf.union(Tile::Edge1, Tile::Edge2);
         assert!(uf.connected(Tile::Edge1, Tile::Edge2));
     }
+
+    #[test]
+    fn snapshot() {
+        let mut uf = UnionFind::new(3);
+        uf.union(Tile::Edge1, Tile::Regular(0, 1));
+        let snapshot = uf.snapshot();
+        uf.union(Tile::Regular(1, 1), Tile::Edge2);
+        uf.union(Tile::Regular(0, 1), Tile::Regular(1, 1));
+        assert!(uf.connected(Tile::Edge1, Tile::Edge2));
+        uf.restore(snapshot);
+        assert!(!uf.connected(Tile::Edge1, Tile::Edge2));
+        assert!(!uf.connected(Tile::Regular(1, 1), Tile::Edge2));
+        assert!(uf.connected(Tile::Edge1, Tile::Regular(0, 1)));
+        assert!(!uf.connected(Tile::Regular(3, 0), Tile::Regular(3, 0)));
+        uf.restore(Snapshot::default());
+        assert!(!uf.connected(Tile::Edge1, Tile::Regular(0, 1)));
+    }
+
+    #[test]
+    fn restore_set() {
+        let mut uf = UnionFind::new(3);
+        let before = uf.snapshot();
+        uf.union(Tile::Edge1, Tile::Regular(0, 0));
+        let mut first = uf.snapshot();
+        uf.union(Tile::Regular(2, 2), Tile::Edge2);
+        let mut second = uf.snapshot();
+        uf.union(Tile::Regular(0, 0), Tile::Regular(1, 0));
+        uf.restore_set(before, Tile::Regular(0, 0), [&mut first, &mut second]);
+        assert!(!uf.connected(Tile::Edge1, Tile::Regular(0, 0)));
+        assert!(!uf.connected(Tile::Regular(0, 0), Tile::Regular(1, 0)));
+        assert!(uf.connected(Tile::Regular(2, 2), Tile::Edge2));
+        assert_eq!(first, before);
+        assert_eq!(second, uf.snapshot());
+        uf.restore(first);
+        assert!(!uf.connected(Tile::Regular(2, 2), Tile::Edge2));
+    }
+
+    #[test]
+    fn compression() {
+        let mut uf = UnionFind::new(3);
+        let chain = [(0, 0), (0, 1), (0, 2), (1, 2), (2, 2)].map(|(r, c)| Tile::Regular(r, c));
+        for pair in chain.windows(2) {
+            uf.union(pair[0], pair[1]);
+        }
+        let before = uf.snapshot();
+        uf.union(Tile::Regular(0, 0), Tile::Edge1);
+        let root = uf.find(Tile::Regular(0, 0)).unwrap();
+        for &t in &chain {
+            let idx = uf.index(t).unwrap();
+            assert!([idx, root].contains(&(uf.set[idx].parent as usize)));
+        }
+        uf.restore(before);
+        assert!(!uf.connected(Tile::Regular(2, 2), Tile::Edge1));
+        assert!(uf.connected(Tile::Regular(2, 2), Tile::Regular(0, 0)));
+    }
 }
@@ -0,0 +1,157 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![allow(clippy::implicit_return, clippy::question_mark_used)]
+
+//! Plays two engine configurations against each other and estimates their Elo difference.
+
+use pincerhex_bot::{
+    selfplay::{play_game, GameOptions, MatchScore},
+    Colour, EngineKind, EvalParams, SeededRng,
+};
+use pincerhex_core::{MAX_SIZE, MIN_OPENING_SIZE};
+
+#[path = "../common.rs"]
+mod common;
+
+use common::{load_params, log_seed, Error};
+
+/// One side of the match
+struct Player {
+    engine: EngineKind,
+    params: Option<String>,
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self {
+            engine: EngineKind::Potential,
+            params: None,
+        }
+    }
+}
+
+struct Options {
+    a: Player,
+    b: Player,
+    games: u32,
+    size: i8,
+    swap_rule: bool,
+    random_moves: u16,
+    seed: Option<u64>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            a: Player::default(),
+            b: Player::default(),
+            games: 100,
+            size: 9,
+            swap_rule: true,
+            random_moves: 2,
+            seed: None,
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> Option<Options> {
+    let mut options = Options::default();
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--engine-a" => options.a.engine = EngineKind::try_from(rest.next()?.as_str()).ok()?,
+            "--engine-b" => options.b.engine = EngineKind::try_from(rest.next()?.as_str()).ok()?,
+            "--params-a" => options.a.params = Some(rest.next()?.clone()),
+            "--params-b" => options.b.params = Some(rest.next()?.clone()),
+            "--games" => options.games = rest.next()?.parse().ok()?,
+            "--size" => {
+                options.size = rest
+                    .next()?
+                    .parse()
+                    .ok()
+                    .filter(|s| (MIN_OPENING_SIZE..=MAX_SIZE).contains(s))?;
+            }
+            "--noswap" => options.swap_rule = false,
+            "--random-moves" => options.random_moves = rest.next()?.parse().ok()?,
+            "--seed" => options.seed = Some(rest.next()?.parse().ok()?),
+            _ => return None,
+        }
+    }
+    Some(options)
+}
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = std::env::args().collect();
+    let options = parse_args(&args).ok_or_else(|| {
+        Error(format!(
+            "usage: {} [--engine-a <engine>] [--params-a <file>] [--engine-b <engine>] \
+             [--params-b <file>] [--games <n>] [--size <size>] [--noswap] \
+             [--random-moves <n>] [--seed <seed>]",
+            args[0]
+        ))
+    })?;
+    let seed = log_seed(options.seed);
+
+    let a_params = options
+        .a
+        .params
+        .as_deref()
+        .map_or(Ok(EvalParams::DEFAULT), load_params)?;
+    let b_params = options
+        .b
+        .params
+        .as_deref()
+        .map_or(Ok(EvalParams::DEFAULT), load_params)?;
+    let mut a = options.a.engine.engine(a_params);
+    let mut b = options.b.engine.engine(b_params);
+    let game_options = GameOptions {
+        size: options.size,
+        swap_rule: options.swap_rule,
+        random_moves: options.random_moves,
+    };
+    let mut rng = SeededRng::new(seed);
+
+    let mut score = MatchScore::default();
+    let mut as_black = MatchScore::default();
+    let mut as_white = MatchScore::default();
+    for game in 0..options.games {
+        let a_first = game % 2 == 0;
+        let record = if a_first {
+            play_game(a.as_mut(), b.as_mut(), game_options, &mut rng)
+        } else {
+            play_game(b.as_mut(), a.as_mut(), game_options, &mut rng)
+        };
+        let a_won = (record.winning_player == 0) == a_first;
+        let a_played_black = (record.winner == Colour::Black) == a_won;
+        let by_colour = if a_played_black {
+            &mut as_black
+        } else {
+            &mut as_white
+        };
+        if a_won {
+            score.wins += 1;
+            by_colour.wins += 1;
+        } else {
+            score.losses += 1;
+            by_colour.losses += 1;
+        }
+        eprintln!(
+            "game {}: {} won as {} in {} moves",
+            game + 1,
+            if a_won { "a" } else { "b" },
+            record.winner,
+            record.moves.len()
+        );
+    }
+
+    let (low, high) = score.elo_interval();
+    println!(
+        "a ({}) vs b ({}): {score}",
+        options.a.engine, options.b.engine
+    );
+    println!("a as black: {as_black}, a as white: {as_white}");
+    println!(
+        "elo difference: {:+.1} (95% confidence: {low:+.1} to {high:+.1})",
+        score.elo()
+    );
+    Ok(())
+}
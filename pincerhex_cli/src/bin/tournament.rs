@@ -0,0 +1,356 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![allow(clippy::implicit_return, clippy::question_mark_used)]
+
+//! Round-robin tournament between engine executables that speak the `pincerhex_cli` protocol.
+//! Engines are started as `<program> <colour> [args]`, like the UAIS competition does. Every move
+//! is checked against a strict [`State`], and a player that makes an illegal move, crashes or runs
+//! out of time loses the game.
+
+use std::{
+    fmt::Write as _,
+    io::{BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver},
+    time::Duration,
+};
+
+use pincerhex_bot::{Colour, Move, PieceState};
+use pincerhex_core::{Tile, MAX_SIZE, MIN_OPENING_SIZE};
+use pincerhex_state::State;
+
+#[path = "../common.rs"]
+mod common;
+
+use common::Error;
+
+struct Options {
+    /// Games played by every pair of engines
+    games: u32,
+    size: i8,
+    swap_rule: bool,
+    timeout: Duration,
+    records: Option<String>,
+    engines: Vec<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            games: 2,
+            size: 11,
+            swap_rule: true,
+            timeout: Duration::from_secs(30),
+            records: None,
+            engines: Vec::new(),
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> Option<Options> {
+    let mut options = Options::default();
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--games" => options.games = rest.next()?.parse().ok()?,
+            "--size" => {
+                options.size = rest
+                    .next()?
+                    .parse()
+                    .ok()
+                    .filter(|s| (MIN_OPENING_SIZE..=MAX_SIZE).contains(s))?;
+            }
+            "--noswap" => options.swap_rule = false,
+            "--timeout" => options.timeout = Duration::from_millis(rest.next()?.parse().ok()?),
+            "--records" => options.records = Some(rest.next()?.clone()),
+            s if s.starts_with("--") => return None,
+            command => options.engines.push(command.to_owned()),
+        }
+    }
+    (options.engines.len() >= 2).then_some(options)
+}
+
+/// Names engines after their executable, numbering duplicates
+fn engine_names(commands: &[String]) -> Vec<String> {
+    let base = commands
+        .iter()
+        .map(|c| {
+            let program = c.split_whitespace().next().unwrap_or_default();
+            std::path::Path::new(program)
+                .file_name()
+                .map_or_else(|| program.to_owned(), |n| n.to_string_lossy().into_owned())
+        })
+        .collect::<Vec<_>>();
+    base.iter()
+        .enumerate()
+        .map(|(idx, name)| {
+            if base.iter().filter(|&n| n == name).count() > 1 {
+                format!("{name}#{}", idx + 1)
+            } else {
+                name.clone()
+            }
+        })
+        .collect()
+}
+
+/// A running engine
+struct Process {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl Process {
+    fn spawn(command: &str, colour: Colour) -> std::io::Result<Self> {
+        let mut parts = command.split_whitespace();
+        let program = parts.next().unwrap_or_default();
+        let mut child = Command::new(program)
+            .arg(colour.to_string().to_lowercase())
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("piped stdin");
+        let stdout = child.stdout.take().expect("piped stdout");
+
+        // Lines are read on a separate thread so a hanging engine can be timed out
+        let (tx, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            child,
+            stdin,
+            lines,
+        })
+    }
+
+    fn send(&mut self, command: &str) -> std::io::Result<()> {
+        writeln!(self.stdin, "{command}")?;
+        self.stdin.flush()
+    }
+
+    /// Next non-empty line of output
+    fn receive(&self, timeout: Duration) -> Option<String> {
+        loop {
+            let line = self.lines.recv_timeout(timeout).ok()?;
+            let line = line.trim();
+            if !line.is_empty() {
+                return Some(line.to_owned());
+            }
+        }
+    }
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// A finished game. Players are numbered by their index into the engine list.
+struct GameRecord {
+    /// Player that moved first
+    first: usize,
+    second: usize,
+    moves: Vec<Move>,
+    winner: usize,
+    /// Why the game ended if nobody connected their edges
+    forfeit: Option<String>,
+    /// Shortest chain of the winner's stones if they connected their edges
+    path: Vec<Tile>,
+}
+
+fn parse_move(reply: &str) -> Option<Move> {
+    if reply == "swap" {
+        Some(Move::Swap)
+    } else {
+        Tile::try_from(reply).ok().map(Move::Move)
+    }
+}
+
+fn play_game(options: &Options, first: usize, second: usize) -> GameRecord {
+    let players = [first, second];
+    let mut record = GameRecord {
+        first,
+        second,
+        moves: Vec::new(),
+        winner: second,
+        forfeit: None,
+        path: Vec::new(),
+    };
+    let mut colours = [Colour::Black, Colour::White];
+    let init = if options.swap_rule {
+        format!("init_board {}", options.size)
+    } else {
+        format!("init_board {} noswap", options.size)
+    };
+
+    let mut processes = Vec::with_capacity(2);
+    for (idx, &player) in players.iter().enumerate() {
+        let process = Process::spawn(&options.engines[player], colours[idx])
+            .and_then(|mut p| p.send(&init).map(|()| p));
+        match process {
+            Ok(p) => processes.push(p),
+            Err(e) => {
+                record.winner = players[1 - idx];
+                record.forfeit = Some(format!("failed to start: {e}"));
+                return record;
+            }
+        }
+    }
+
+    let mut state = State::with_swap_rule(options.size, options.swap_rule);
+    state.set_strict(true);
+    let mut to_move = 0;
+    loop {
+        let opponent = 1 - to_move;
+        let reply = processes[to_move]
+            .send("make_move")
+            .ok()
+            .and_then(|()| processes[to_move].receive(options.timeout));
+        let result = match reply.as_deref().map(|r| (r, parse_move(r))) {
+            None => Err(String::from("no reply")),
+            Some((r, None)) => Err(format!("invalid move `{r}`")),
+            Some((_, Some(Move::Swap))) => state
+                .swap()
+                .map(|()| {
+                    colours.swap(0, 1);
+                    Move::Swap
+                })
+                .map_err(|e| e.to_string()),
+            Some((r, Some(Move::Move(tile)))) => state
+                .try_place_piece(tile, PieceState::Colour(colours[to_move]))
+                .map(|()| Move::Move(tile))
+                .map_err(|e| format!("illegal move `{r}`: {e}")),
+        };
+        let mv = match result {
+            Ok(mv) => mv,
+            Err(reason) => {
+                record.winner = players[opponent];
+                record.forfeit = Some(reason);
+                return record;
+            }
+        };
+        record.moves.push(mv);
+
+        let told = match mv {
+            Move::Swap => processes[opponent].send("swap"),
+            Move::Move(tile) => processes[opponent].send(&format!("seto {tile}")),
+        };
+        if told.is_err() {
+            record.winner = players[to_move];
+            record.forfeit = Some(String::from("opponent crashed"));
+            return record;
+        }
+
+        if let Some((winner, path)) = state.winning_path() {
+            record.winner = if colours[0] == winner { first } else { second };
+            record.path = path;
+            return record;
+        }
+        to_move = opponent;
+    }
+}
+
+fn format_record(record: &GameRecord, names: &[String]) -> String {
+    let mut line = format!(
+        "{} vs {}: {} wins",
+        names[record.first], names[record.second], names[record.winner]
+    );
+    if let Some(reason) = &record.forfeit {
+        let _ = write!(line, " ({reason})");
+    }
+    line.push(':');
+    for mv in &record.moves {
+        match mv {
+            Move::Move(tile) => {
+                let _ = write!(line, " {tile}");
+            }
+            Move::Swap => line.push_str(" swap"),
+        }
+    }
+    if !record.path.is_empty() {
+        line.push_str(" | path:");
+        for tile in &record.path {
+            let _ = write!(line, " {tile}");
+        }
+    }
+    line
+}
+
+fn crosstable(wins: &[Vec<u32>], names: &[String]) -> String {
+    let width = names
+        .iter()
+        .map(String::len)
+        .max()
+        .unwrap_or_default()
+        .max(6);
+    let mut table = format!("{:width$}", "");
+    for name in names {
+        let _ = write!(table, " {name:>width$}");
+    }
+    let _ = writeln!(table, " {:>width$}", "score");
+    for (i, name) in names.iter().enumerate() {
+        let _ = write!(table, "{name:width$}");
+        for (j, row) in wins.iter().enumerate() {
+            let cell = if i == j {
+                String::from("-")
+            } else {
+                format!("{}-{}", wins[i][j], row[i])
+            };
+            let _ = write!(table, " {cell:>width$}");
+        }
+        let won = wins[i].iter().sum::<u32>();
+        let played = won + wins.iter().map(|row| row[i]).sum::<u32>();
+        let _ = writeln!(table, " {:>width$}", format!("{won}/{played}"));
+    }
+    table
+}
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = std::env::args().collect();
+    let options = parse_args(&args).ok_or_else(|| {
+        Error(format!(
+            "usage: {} [--games <n per pair>] [--size <size>] [--noswap] \
+             [--timeout <milliseconds per move>] [--records <file>] <engine> <engine>...",
+            args[0]
+        ))
+    })?;
+    let names = engine_names(&options.engines);
+    let n = names.len();
+
+    let mut wins = vec![vec![0; n]; n];
+    let mut records = Vec::new();
+    for i in 0..n {
+        for j in i + 1..n {
+            for game in 0..options.games {
+                let (first, second) = if game % 2 == 0 { (i, j) } else { (j, i) };
+                let record = play_game(&options, first, second);
+                let loser = if record.winner == first {
+                    second
+                } else {
+                    first
+                };
+                wins[record.winner][loser] += 1;
+                let line = format_record(&record, &names);
+                eprintln!("{line}");
+                records.push(line);
+            }
+        }
+    }
+
+    if let Some(path) = &options.records {
+        let mut contents = records.join("\n");
+        contents.push('\n');
+        std::fs::write(path, contents).map_err(|e| Error(format!("{path}: {e}")))?;
+    }
+    print!("{}", crosstable(&wins, &names));
+    Ok(())
+}
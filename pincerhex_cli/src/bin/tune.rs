@@ -0,0 +1,208 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![allow(
+    clippy::implicit_return,
+    clippy::question_mark_used,
+    clippy::cast_precision_loss
+)]
+
+//! Tunes the evaluator parameters with SPSA (simultaneous perturbation stochastic
+//! approximation). Every iteration nudges all tuned parameters in a random direction, plays the
+//! `+` copy against the `-` copy and moves the parameters towards the side that won more games.
+
+use pincerhex_bot::{
+    selfplay::{play_game, GameOptions, MatchScore},
+    EngineKind, EvalParams, Rand, SeededRng,
+};
+use pincerhex_core::{MAX_SIZE, MIN_OPENING_SIZE};
+
+#[path = "../common.rs"]
+mod common;
+
+use common::{load_params, log_seed, Error};
+
+/// Perturbation size relative to a parameter's starting value
+const PERTURBATION: f32 = 0.1;
+/// Step size relative to a parameter's starting value
+const LEARNING_RATE: f32 = 0.2;
+/// Parameters left out by default. `rounds` only trades speed for accuracy.
+const SKIPPED: &[&str] = &["rounds"];
+
+struct Options {
+    iterations: u32,
+    /// Games per board size in each iteration
+    games: u32,
+    sizes: Vec<i8>,
+    engine: EngineKind,
+    swap_rule: bool,
+    seed: Option<u64>,
+    params: Option<String>,
+    tune: Option<Vec<String>>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            iterations: 100,
+            games: 4,
+            sizes: vec![5, 7, 9],
+            engine: EngineKind::Potential,
+            swap_rule: true,
+            seed: None,
+            params: None,
+            tune: None,
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> Option<Options> {
+    let mut options = Options::default();
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--iterations" => options.iterations = rest.next()?.parse().ok()?,
+            "--games" => options.games = rest.next()?.parse().ok()?,
+            "--sizes" => {
+                options.sizes = rest
+                    .next()?
+                    .split(',')
+                    .map(|s| {
+                        s.parse()
+                            .ok()
+                            .filter(|size| (MIN_OPENING_SIZE..=MAX_SIZE).contains(size))
+                    })
+                    .collect::<Option<_>>()?;
+            }
+            "--engine" => options.engine = EngineKind::try_from(rest.next()?.as_str()).ok()?,
+            "--noswap" => options.swap_rule = false,
+            "--seed" => options.seed = Some(rest.next()?.parse().ok()?),
+            "--params" => options.params = Some(rest.next()?.clone()),
+            "--tune" => {
+                let names = rest
+                    .next()?
+                    .split(',')
+                    .map(String::from)
+                    .collect::<Vec<_>>();
+                if names
+                    .iter()
+                    .any(|n| !EvalParams::NAMES.contains(&n.as_str()))
+                {
+                    return None;
+                }
+                options.tune = Some(names);
+            }
+            _ => return None,
+        }
+    }
+    Some(options)
+}
+
+/// Plays `a` against `b` on board of every size, alternating who goes first
+fn play_match(
+    options: &Options,
+    a: EvalParams,
+    b: EvalParams,
+    games: u32,
+    rng: &mut SeededRng,
+) -> MatchScore {
+    let mut score = MatchScore::default();
+    let mut a_engine = options.engine.engine(a);
+    let mut b_engine = options.engine.engine(b);
+    for &size in &options.sizes {
+        let game_options = GameOptions {
+            size,
+            swap_rule: options.swap_rule,
+            random_moves: 0,
+        };
+        for game in 0..games {
+            let a_first = game % 2 == 0;
+            let record = if a_first {
+                play_game(a_engine.as_mut(), b_engine.as_mut(), game_options, rng)
+            } else {
+                play_game(b_engine.as_mut(), a_engine.as_mut(), game_options, rng)
+            };
+            if (record.winning_player == 0) == a_first {
+                score.wins += 1;
+            } else {
+                score.losses += 1;
+            }
+        }
+    }
+    score
+}
+
+fn with_values(base: EvalParams, names: &[String], values: &[f32]) -> EvalParams {
+    let mut params = base;
+    for (name, &value) in names.iter().zip(values) {
+        params.set(name, value.max(0.));
+    }
+    params
+}
+
+fn tune(options: &Options, initial: EvalParams, seed: u64) -> EvalParams {
+    let names = options.tune.clone().unwrap_or_else(|| {
+        EvalParams::NAMES
+            .iter()
+            .filter(|n| !SKIPPED.contains(n))
+            .map(|&n| String::from(n))
+            .collect()
+    });
+    let mut values = names
+        .iter()
+        .map(|n| initial.get(n).expect("known parameter"))
+        .collect::<Vec<_>>();
+    let scales = values.iter().map(|v| v.abs().max(1.)).collect::<Vec<_>>();
+    let mut rng = SeededRng::new(seed);
+    let stability = options.iterations as f32 / 10.;
+
+    for iteration in 1..=options.iterations {
+        let k = (iteration - 1) as f32;
+        let step = LEARNING_RATE / (k + 1. + stability).powf(0.602);
+        let perturbation = PERTURBATION / (k + 1.).powf(0.101);
+        let delta = values
+            .iter()
+            .map(|_| if rng.next() < 0.5 { -1. } else { 1. })
+            .collect::<Vec<f32>>();
+
+        let shifted = |sign: f32| {
+            let shifted = values
+                .iter()
+                .zip(&scales)
+                .zip(&delta)
+                .map(|((v, s), d)| v + sign * perturbation * s * d)
+                .collect::<Vec<_>>();
+            with_values(initial, &names, &shifted)
+        };
+        let score = play_match(options, shifted(1.), shifted(-1.), options.games, &mut rng);
+
+        for ((value, scale), d) in values.iter_mut().zip(&scales).zip(&delta) {
+            *value = (*value + step * score.result() * scale * d).max(0.);
+        }
+        eprintln!("iteration {iteration}: plus vs minus {score}");
+    }
+    with_values(initial, &names, &values)
+}
+
+fn main() -> Result<(), Error> {
+    let args: Vec<String> = std::env::args().collect();
+    let options = parse_args(&args).ok_or_else(|| {
+        Error(format!(
+            "usage: {} [--iterations <n>] [--games <n per size>] [--sizes <5,7,9>] \
+             [--engine <potential|mcts|mcts-guided|alphabeta>] [--noswap] [--seed <seed>] \
+             [--params <file>] [--tune <name,name,...>]",
+            args[0]
+        ))
+    })?;
+    let initial = options
+        .params
+        .as_deref()
+        .map_or(Ok(EvalParams::DEFAULT), load_params)?;
+
+    let seed = log_seed(options.seed);
+    let tuned = tune(&options, initial, seed);
+    let mut rng = SeededRng::new(seed.wrapping_add(1));
+    // Verification match with twice as many games as a single iteration
+    let score = play_match(&options, tuned, initial, options.games * 2, &mut rng);
+    eprintln!("tuned vs initial: {score}");
+    print!("{tuned}");
+    Ok(())
+}
@@ -0,0 +1,35 @@
+//! Helpers shared by the command line tools
+
+// Not every binary uses every helper
+#![allow(dead_code)]
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+use pincerhex_bot::EvalParams;
+
+/// Printed by `main` through `Debug`
+pub struct Error(pub String);
+
+impl core::fmt::Debug for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Reads evaluator parameters in the format printed by `tune`
+pub fn load_params(path: &str) -> Result<EvalParams, Error> {
+    std::fs::read_to_string(path)
+        .map_err(|e| Error(format!("invalid params file: {path}: {e}")))?
+        .parse::<EvalParams>()
+        .map_err(|e| Error(format!("invalid params file: {path}: {e}")))
+}
+
+/// `seed`, or a random one if there is none. Logged so runs can be replayed with `--seed`.
+pub fn log_seed(seed: Option<u64>) -> u64 {
+    let seed = seed.unwrap_or_else(|| RandomState::new().build_hasher().finish());
+    eprintln!("seed: {seed}");
+    seed
+}
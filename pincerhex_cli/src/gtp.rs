@@ -0,0 +1,318 @@
+//! Go Text Protocol front-end, using the Hex conventions of `HexGui`: columns are letters, rows
+//! are numbers, black connects top and bottom, and the swap is played as `swap-sides` or
+//! `swap-pieces`.
+
+use std::io::{BufRead, Write};
+
+use pincerhex_bot::{
+    Colour, Engine, EngineKind, EvalParams, Move, PieceState, Rand, SeededRng, SwapKind,
+};
+use pincerhex_core::{first_move, Tile, MAX_SIZE, MIN_OPENING_SIZE};
+use pincerhex_state::{
+    notation::{parse_cell, Cell},
+    State, DEFAULT_SIZE,
+};
+
+const COMMANDS: &[&str] = &[
+    "boardsize",
+    "clear_board",
+    "final_score",
+    "genmove",
+    "hexgui-analyze_commands",
+    "known_command",
+    "list_commands",
+    "name",
+    "play",
+    "protocol_version",
+    "quit",
+    "showboard",
+    "undo",
+    "version",
+];
+
+const ANALYZE_COMMANDS: &str = "string/Show Board/showboard\nstring/Final Score/final_score";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GtpMove {
+    Tile(Tile),
+    /// The players switch colours and the board stays the same
+    SwapSides,
+    /// The opening stone is mirrored and changes colour
+    SwapPieces,
+    Resign,
+}
+
+impl GtpMove {
+    fn parse(s: &str, size: i8) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "swap-sides" | "swap" => Some(Self::SwapSides),
+            "swap-pieces" => Some(Self::SwapPieces),
+            "resign" => Some(Self::Resign),
+            s => parse_cell(s, size).map(Self::Tile),
+        }
+    }
+}
+
+impl core::fmt::Display for GtpMove {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Tile(t) => write!(f, "{}", Cell(*t)),
+            Self::SwapSides => write!(f, "swap-sides"),
+            Self::SwapPieces => write!(f, "swap-pieces"),
+            Self::Resign => write!(f, "resign"),
+        }
+    }
+}
+
+fn parse_colour(s: &str) -> Option<Colour> {
+    match s.to_lowercase().as_str() {
+        "b" | "black" => Some(Colour::Black),
+        "w" | "white" => Some(Colour::White),
+        _ => None,
+    }
+}
+
+pub struct Gtp {
+    size: i8,
+    state: State,
+    resigned: Option<Colour>,
+    engine: Box<dyn Engine>,
+    rng: SeededRng,
+}
+
+enum Response {
+    Success(String),
+    Failure(String),
+    Quit,
+}
+
+impl Gtp {
+    #[must_use]
+    pub fn new(engine: EngineKind, params: EvalParams, seed: u64) -> Self {
+        Self {
+            size: DEFAULT_SIZE,
+            state: State::new(DEFAULT_SIZE),
+            resigned: None,
+            engine: engine.engine(params),
+            rng: SeededRng::new(seed),
+        }
+    }
+
+    /// Answers commands from `input` until `quit` or the end of the input
+    ///
+    /// # Errors
+    /// Will return an `Err` if reading or writing failed
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> std::io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            // Comments and control characters are ignored
+            let line = line.split('#').next().unwrap_or_default();
+            let mut words = line
+                .split_whitespace()
+                .filter(|w| !w.chars().any(char::is_control))
+                .peekable();
+            let id = words
+                .next_if(|w| w.bytes().all(|b| b.is_ascii_digit()))
+                .unwrap_or_default();
+            let Some(command) = words.next() else {
+                continue;
+            };
+            let args = words.collect::<Vec<_>>();
+            match self.process_command(command, &args) {
+                Response::Success(s) => write!(output, "={id} {s}\n\n")?,
+                Response::Failure(s) => write!(output, "?{id} {s}\n\n")?,
+                Response::Quit => {
+                    write!(output, "={id}\n\n")?;
+                    output.flush()?;
+                    break;
+                }
+            }
+            output.flush()?;
+        }
+        Ok(())
+    }
+
+    fn process_command(&mut self, command: &str, args: &[&str]) -> Response {
+        let ok = |s: &str| Response::Success(String::from(s));
+        let err = |s: &str| Response::Failure(String::from(s));
+        match command {
+            "protocol_version" => ok("2"),
+            "name" => ok("pincerhex"),
+            "version" => ok(env!("CARGO_PKG_VERSION")),
+            "list_commands" => ok(&COMMANDS.join("\n")),
+            "known_command" => ok(&args
+                .first()
+                .is_some_and(|c| COMMANDS.contains(c))
+                .to_string()),
+            "hexgui-analyze_commands" => ok(ANALYZE_COMMANDS),
+            "quit" => Response::Quit,
+            "boardsize" => {
+                let sizes = args
+                    .iter()
+                    .map(|s| {
+                        s.parse::<i8>()
+                            .ok()
+                            .filter(|&s| (2..=MAX_SIZE).contains(&s))
+                    })
+                    .collect::<Option<Vec<_>>>();
+                match sizes.as_deref() {
+                    Some(&[size]) => self.reset(size),
+                    Some(&[cols, rows]) if cols == rows => self.reset(cols),
+                    Some(&[_, _]) => return err("board must be square"),
+                    _ => return err("invalid board size"),
+                }
+                ok("")
+            }
+            "clear_board" => {
+                self.reset(self.size);
+                ok("")
+            }
+            "showboard" => ok(&format!("\n{}", self.state.get_pretty())),
+            "play" => {
+                let (Some(colour), Some(mv)) = (
+                    args.first().and_then(|c| parse_colour(c)),
+                    args.get(1).and_then(|m| GtpMove::parse(m, self.size)),
+                ) else {
+                    return err("syntax error");
+                };
+                match self.play(colour, mv) {
+                    Ok(()) => ok(""),
+                    Err(e) => err(e),
+                }
+            }
+            "genmove" => {
+                let Some(colour) = args.first().and_then(|c| parse_colour(c)) else {
+                    return err("syntax error");
+                };
+                if self.winner().is_some() {
+                    return err("game is over");
+                }
+                let mv = self.generate(colour);
+                self.play(colour, mv).expect("generated a legal move");
+                ok(&mv.to_string())
+            }
+            "undo" => {
+                if self.resigned.take().is_some() || self.state.undo().is_some() {
+                    ok("")
+                } else {
+                    err("cannot undo")
+                }
+            }
+            "final_score" => match self.winner() {
+                Some(Colour::Black) => ok("B+"),
+                Some(Colour::White) => ok("W+"),
+                None => err("game is not over"),
+            },
+            _ => err("unknown command"),
+        }
+    }
+
+    fn reset(&mut self, size: i8) {
+        self.size = size;
+        self.state = State::new(size);
+        self.resigned = None;
+    }
+
+    fn winner(&self) -> Option<Colour> {
+        self.resigned
+            .map(Colour::opponent)
+            .or_else(|| self.state.check_win())
+    }
+
+    /// A swap is only legal as the second move of the game
+    fn can_swap(&self) -> bool {
+        matches!(self.state.history(), [entry] if matches!(entry.mv, Move::Move(_)))
+    }
+
+    fn play(&mut self, colour: Colour, mv: GtpMove) -> Result<(), &'static str> {
+        if self.resigned.is_some() {
+            return Err("game is over");
+        }
+        let kind = match mv {
+            GtpMove::Tile(tile) => {
+                return self
+                    .state
+                    .try_place_piece(tile, PieceState::Colour(colour))
+                    .map_err(|_| "cell is occupied");
+            }
+            GtpMove::Resign => {
+                self.resigned = Some(colour);
+                return Ok(());
+            }
+            GtpMove::SwapSides => SwapKind::Sides,
+            GtpMove::SwapPieces => SwapKind::Pieces,
+        };
+        if !self.can_swap() {
+            return Err("swap is not allowed");
+        }
+        self.state.set_swap_kind(kind);
+        self.state.swap().map_err(|_| "invalid swap")
+    }
+
+    fn generate(&mut self, colour: Colour) -> GtpMove {
+        // Small boards have no room for a random opening
+        if self.state.history().is_empty() && self.size >= MIN_OPENING_SIZE {
+            let (i, j) = first_move(self.size, &mut self.rng);
+            return GtpMove::Tile(Tile::Regular(i, j));
+        }
+        if self.can_swap() && self.state.should_swap(&mut self.rng) {
+            return GtpMove::SwapSides;
+        }
+        let starting = match self.state.history().first().map(|e| e.piece) {
+            Some(PieceState::Colour(c)) => c,
+            _ => colour,
+        };
+        let mv = self
+            .engine
+            .best_move(
+                self.state.get_board(),
+                colour,
+                starting,
+                self.state.move_count(),
+                &mut self.rng as &mut dyn Rand,
+            )
+            .mv;
+        GtpMove::Tile(mv)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(commands: &str) -> String {
+        let mut output = Vec::new();
+        Gtp::new(EngineKind::Potential, EvalParams::DEFAULT, 1)
+            .run(commands.as_bytes(), &mut output)
+            .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn plays_and_undoes_moves() {
+        let out = run("1 boardsize 3\nplay b b1\nplay w b1\nplay w swap-pieces\nundo\nshowboard\n");
+        assert_eq!(
+            out,
+            "=1 \n\n= \n\n? cell is occupied\n\n= \n\n= \n\n= \n. B . \n . . . \n  . . . \n------------------\n\n"
+        );
+    }
+
+    #[test]
+    fn swaps() {
+        let out = run(
+            "boardsize 3\nplay b a2\nplay w swap-piece\nplay w swap-pieces\nshowboard\n\
+             play b swap-sides\nundo\nplay w swap\nshowboard\n",
+        );
+        assert_eq!(
+            out,
+            "= \n\n= \n\n? syntax error\n\n= \n\n= \n. W . \n . . . \n  . . . \n------------------\n\n\
+             ? swap is not allowed\n\n= \n\n= \n\n= \n. . . \n B . . \n  . . . \n------------------\n\n"
+        );
+    }
+
+    #[test]
+    fn detects_the_winner() {
+        let out = run("boardsize 2\nplay b a1\nplay w b1\nfinal_score\nplay b a2\nfinal_score\n");
+        assert!(out.ends_with("? game is not over\n\n= \n\n= B+\n\n"));
+    }
+}
@@ -0,0 +1,139 @@
+//! JSON lines protocol. Every request is an object like
+//! `{"id": 1, "command": "seto", "args": ["a1"]}` and gets exactly one response object on its
+//! own line, either `{"id": 1, "result": ..., "board": "...", "winner": ...}` or
+//! `{"id": 1, "error": "..."}`.
+
+use std::io::{BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use pincerhex_bot::{HexBot, Move, Winner};
+
+use crate::{process_command, HexBotOutput};
+
+#[derive(Deserialize)]
+struct Request {
+    /// Echoed back in the response
+    #[serde(default)]
+    id: Value,
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Response {
+    Success {
+        id: Value,
+        result: Value,
+        /// Compressed board after the command
+        board: String,
+        /// `"bot"` or `"opponent"` once the game is won
+        winner: Option<&'static str>,
+    },
+    Failure {
+        id: Value,
+        error: String,
+    },
+}
+
+const fn winner_name(winner: Option<Winner>) -> Option<&'static str> {
+    match winner {
+        Some(Winner::Bot) => Some("bot"),
+        Some(Winner::Opponent) => Some("opponent"),
+        None => None,
+    }
+}
+
+fn result(output: HexBotOutput) -> Value {
+    match output {
+        HexBotOutput::Empty => Value::Null,
+        HexBotOutput::Move(Move::Move(mv)) => Value::String(mv.to_string()),
+        HexBotOutput::Move(Move::Swap) => Value::String(String::from("swap")),
+        HexBotOutput::CheckWin(w) => json!(match w {
+            Some(Winner::Bot) => 1,
+            Some(Winner::Opponent) => -1,
+            None => 0,
+        }),
+        HexBotOutput::String(s) => Value::String(s),
+        HexBotOutput::Analysis(e) => json!({ "move": e.mv.to_string(), "score": e.score }),
+        HexBotOutput::Path(path) => path.iter().map(ToString::to_string).collect(),
+    }
+}
+
+fn respond(bot: &mut HexBot, line: &str) -> Response {
+    let request = match serde_json::from_str::<Request>(line) {
+        Ok(request) => request,
+        Err(e) => {
+            return Response::Failure {
+                id: Value::Null,
+                error: format!("invalid request: {e}"),
+            }
+        }
+    };
+    let args = request.args.iter().map(String::as_str).collect::<Vec<_>>();
+    match process_command(bot, &request.command, &args) {
+        Ok(output) => Response::Success {
+            id: request.id,
+            result: result(output),
+            board: bot.get_compressed(),
+            winner: winner_name(bot.check_win()),
+        },
+        Err(e) => Response::Failure {
+            id: request.id,
+            error: e.to_string(),
+        },
+    }
+}
+
+/// Answers every line of `input` with one line of JSON
+///
+/// # Errors
+/// Will return an `Err` if reading or writing failed
+pub fn run(bot: &mut HexBot, input: impl BufRead, mut output: impl Write) -> std::io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = serde_json::to_string(&respond(bot, &line))?;
+        writeln!(output, "{response}")?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pincerhex_bot::Colour;
+
+    #[test]
+    fn responses() {
+        let mut bot = HexBot::with_seed(Colour::Black, 1);
+        let mut output = Vec::new();
+        let input = r#"{"id": 1, "command": "init_board", "args": ["2"]}
+{"id": "a", "command": "sety", "args": ["a1"]}
+{"command": "sety", "args": ["b1"]}
+{"id": 3, "command": "seto"}
+{"id": 4, "command": "analyze"}
+not json
+"#;
+        run(&mut bot, input.as_bytes(), &mut output).unwrap();
+        let lines = String::from_utf8(output).unwrap();
+        let lines = lines.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[..5],
+            [
+                r#"{"id":1,"result":null,"board":"..|..|","winner":null}"#,
+                r#"{"id":"a","result":null,"board":"B.|..|","winner":null}"#,
+                r#"{"id":null,"result":null,"board":"B.|B.|","winner":"bot"}"#,
+                r#"{"id":3,"error":"empty move"}"#,
+                r#"{"id":4,"error":"the game is over"}"#,
+            ]
+        );
+        assert!(lines[5].starts_with(r#"{"id":null,"error":"invalid request"#));
+    }
+}
@@ -1,9 +1,27 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
 #![allow(clippy::implicit_return, clippy::question_mark_used)]
 
+mod common;
+mod gtp;
+mod json;
+
+use std::{
+    io::{BufRead, Write},
+    time::Duration,
+};
+
 use rustyline::{self, error::ReadlineError, Editor};
 
-use pincerhex_bot::{BotError, Colour, HexBot, Move, PieceState, Winner};
+use pincerhex_state::{
+    notation,
+    sgf::{self, Sgf},
+};
+
+use pincerhex_bot::{
+    BotError, Colour, EngineKind, EvalParams, Evaluation, HexBot, Move, PieceState, SwapKind,
+    Winner,
+};
+use pincerhex_core::{Tile, MAX_SIZE};
 
 #[allow(dead_code)]
 const HISTFILE: &str = "history.txt";
@@ -12,16 +30,32 @@ enum REPLError {
     InvalidCommand,
     Usage(Usage),
     Bot(BotError),
+    File(String, std::io::Error),
+    Sgf(sgf::Error),
+    Notation(notation::Error),
 }
 
 enum Usage {
     InitBoard,
+    Engine,
+    Time,
+    SaveGame,
+    LoadGame,
+    LoadMoves,
 }
 
 impl core::fmt::Display for Usage {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
-            Self::InitBoard => write!(f, "usage: init_board <size>"),
+            Self::InitBoard => write!(
+                f,
+                "usage: init_board <size> [noswap | swap-sides | swap-pieces]"
+            ),
+            Self::Engine => write!(f, "usage: engine <potential|mcts|mcts-guided|alphabeta>"),
+            Self::Time => write!(f, "usage: time <milliseconds per move, 0 to disable>"),
+            Self::SaveGame => write!(f, "usage: savegame <file>"),
+            Self::LoadGame => write!(f, "usage: loadgame <file>"),
+            Self::LoadMoves => write!(f, "usage: loadmoves <hexworld url | moves...>"),
         }
     }
 }
@@ -32,6 +66,9 @@ impl core::fmt::Display for REPLError {
             Self::InvalidCommand => write!(f, "invalid command"),
             Self::Usage(u) => write!(f, "{u}"),
             Self::Bot(b) => write!(f, "{b}"),
+            Self::File(path, e) => write!(f, "{path}: {e}"),
+            Self::Sgf(e) => write!(f, "invalid game record: {e}"),
+            Self::Notation(e) => write!(f, "{e}"),
         }
     }
 }
@@ -41,6 +78,9 @@ enum HexBotOutput {
     Move(Move),
     CheckWin(Option<Winner>),
     String(String),
+    Analysis(Evaluation),
+    /// Winning chain of stones
+    Path(Vec<Tile>),
 }
 
 impl core::fmt::Display for HexBotOutput {
@@ -57,6 +97,14 @@ impl core::fmt::Display for HexBotOutput {
                 None => write!(f, "0"),
             },
             Self::String(s) => write!(f, "{s}"),
+            Self::Analysis(e) => match e.score {
+                Some(score) => write!(f, "{} {score}", e.mv),
+                None => write!(f, "{}", e.mv),
+            },
+            Self::Path(path) => {
+                let tiles = path.iter().map(Tile::to_string).collect::<Vec<_>>();
+                write!(f, "{}", tiles.join(" "))
+            }
         }
     }
 }
@@ -77,8 +125,17 @@ fn process_command(
             let size = args
                 .first()
                 .and_then(|s| s.parse::<i8>().ok())
+                .filter(|s| (1..=MAX_SIZE).contains(s))
                 .ok_or(REPLError::Usage(Usage::InitBoard))?;
-            bot.init_board(size);
+            let (swap_rule, swap_kind) = match args.get(1) {
+                None | Some(&"swap-sides") => (true, SwapKind::Sides),
+                Some(&"swap-pieces") => (true, SwapKind::Pieces),
+                Some(&"noswap") => (false, SwapKind::Sides),
+                Some(_) => return Err(REPLError::Usage(Usage::InitBoard)),
+            };
+            bot.set_swap_rule(swap_rule);
+            bot.set_swap_kind(swap_kind);
+            bot.init_board(size)?;
             Ok(HexBotOutput::Empty)
         }
         "b" | "show_board" => Ok(HexBotOutput::String(bot.get_compressed())),
@@ -100,10 +157,77 @@ fn process_command(
             Ok(HexBotOutput::Empty)
         }
         "swap" => {
-            bot.swap();
+            bot.swap()?;
+            Ok(HexBotOutput::Empty)
+        }
+        "undo" => {
+            bot.undo()?;
             Ok(HexBotOutput::Empty)
         }
         "c" | "check_win" => Ok(HexBotOutput::CheckWin(bot.check_win())),
+        "winning_path" => Ok(bot
+            .winning_path()
+            .map_or(HexBotOutput::Empty, HexBotOutput::Path)),
+        "a" | "analyze" => Ok(HexBotOutput::Analysis(bot.analyze()?)),
+        "e" | "engine" => {
+            let engine = args
+                .first()
+                .and_then(|s| EngineKind::try_from(*s).ok())
+                .ok_or(REPLError::Usage(Usage::Engine))?;
+            bot.set_engine(engine);
+            Ok(HexBotOutput::Empty)
+        }
+        "t" | "time" => {
+            let ms = args
+                .first()
+                .and_then(|s| s.parse::<u64>().ok())
+                .ok_or(REPLError::Usage(Usage::Time))?;
+            bot.set_time_per_move((ms > 0).then(|| Duration::from_millis(ms)));
+            Ok(HexBotOutput::Empty)
+        }
+        "savegame" | "loadgame" | "loadmoves" => game_record(bot, command, args),
+        &_ => Err(REPLError::InvalidCommand),
+    }
+}
+
+/// Commands that save or load whole games
+fn game_record(bot: &mut HexBot, command: &str, args: &[&str]) -> Result<HexBotOutput, REPLError> {
+    match command {
+        "savegame" => {
+            let path = args.first().ok_or(REPLError::Usage(Usage::SaveGame))?;
+            let mut record = Sgf::from_state(bot.state()).map_err(REPLError::Sgf)?;
+            let name = Some(String::from("pincerhex"));
+            match bot.colour() {
+                Colour::Black => record.black = name,
+                Colour::White => record.white = name,
+            }
+            std::fs::write(path, record.to_string())
+                .map_err(|e| REPLError::File((*path).to_owned(), e))?;
+            Ok(HexBotOutput::Empty)
+        }
+        "loadgame" => {
+            let path = args.first().ok_or(REPLError::Usage(Usage::LoadGame))?;
+            let state = std::fs::read_to_string(path)
+                .map_err(|e| REPLError::File((*path).to_owned(), e))?
+                .parse::<Sgf>()
+                .and_then(|record| record.to_state())
+                .map_err(REPLError::Sgf)?;
+            bot.load_state(state);
+            Ok(HexBotOutput::Empty)
+        }
+        "loadmoves" => {
+            let moves = args.join(" ");
+            let state = if moves.contains('#') {
+                notation::from_hexworld(&moves)
+            } else if moves.is_empty() {
+                return Err(REPLError::Usage(Usage::LoadMoves));
+            } else {
+                notation::from_little_golem(&moves, bot.state().get_board().size)
+            }
+            .map_err(REPLError::Notation)?;
+            bot.load_state(state);
+            Ok(HexBotOutput::Empty)
+        }
         &_ => Err(REPLError::InvalidCommand),
     }
 }
@@ -123,14 +247,22 @@ fn process_line(bot: &mut HexBot, line: &str) -> Result<HexBotOutput, REPLError>
 
 enum Error {
     Readline(rustyline::error::ReadlineError),
+    Io(std::io::Error),
     Usage(String),
+    Params(common::Error),
 }
 
 impl core::fmt::Debug for Error {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::Readline(err) => write!(f, "{err}"),
-            Self::Usage(s) => write!(f, "usage: {s} <colour>"),
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Usage(s) => write!(
+                f,
+                "usage: {s} <colour | --gtp> [--batch | --json] [--seed <seed>] [--params <file>] \
+                 [--engine <engine>]"
+            ),
+            Self::Params(e) => write!(f, "{e:?}"),
         }
     }
 }
@@ -141,14 +273,101 @@ impl From<rustyline::error::ReadlineError> for Error {
     }
 }
 
+enum Mode {
+    /// UAIS competition protocol, playing as the given colour
+    Uais(Colour),
+    Gtp,
+}
+
+struct Options {
+    mode: Mode,
+    /// Read plain lines from stdin and mark the end of every response
+    batch: bool,
+    /// Read and write JSON lines
+    json: bool,
+    seed: Option<u64>,
+    params: Option<String>,
+    engine: Option<EngineKind>,
+}
+
+fn parse_args(args: &[String]) -> Option<Options> {
+    let mode = match args.get(1)?.as_str() {
+        "--gtp" => Mode::Gtp,
+        colour => Mode::Uais(Colour::try_from(&colour.to_owned()).ok()?),
+    };
+    let mut options = Options {
+        mode,
+        batch: false,
+        json: false,
+        seed: None,
+        params: None,
+        engine: None,
+    };
+    let mut rest = args.iter().skip(2);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--batch" => options.batch = true,
+            "--json" => options.json = true,
+            "--seed" => options.seed = Some(rest.next()?.parse().ok()?),
+            "--params" => options.params = Some(rest.next()?.clone()),
+            "--engine" => options.engine = Some(EngineKind::try_from(rest.next()?.as_str()).ok()?),
+            _ => return None,
+        }
+    }
+    Some(options)
+}
+
 fn main() -> Result<(), Error> {
     let args: Vec<String> = std::env::args().collect();
-    let colour = args
-        .get(1)
-        .and_then(|s| Colour::try_from(s).ok())
-        .ok_or(Error::Usage(args[0].clone()))?;
+    let options = parse_args(&args).ok_or_else(|| Error::Usage(args[0].clone()))?;
+    let params = options
+        .params
+        .as_deref()
+        .map_or(Ok(EvalParams::DEFAULT), common::load_params)
+        .map_err(Error::Params)?;
+
+    let colour = match options.mode {
+        Mode::Uais(colour) => colour,
+        Mode::Gtp => {
+            let seed = common::log_seed(options.seed);
+            let engine = options.engine.unwrap_or(EngineKind::Potential);
+            return gtp::Gtp::new(engine, params, seed)
+                .run(std::io::stdin().lock(), std::io::stdout().lock())
+                .map_err(Error::Io);
+        }
+    };
+    let mut bot = HexBot::with_seed(colour, common::log_seed(options.seed));
+    bot.set_eval_params(params);
+    if let Some(engine) = options.engine {
+        bot.set_engine(engine);
+    }
+    if options.json {
+        json::run(&mut bot, std::io::stdin().lock(), std::io::stdout().lock()).map_err(Error::Io)
+    } else if options.batch {
+        batch(&mut bot, std::io::stdin().lock(), std::io::stdout().lock()).map_err(Error::Io)
+    } else {
+        repl(&mut bot)
+    }
+}
+
+/// Answers every line of `input` with `= <output>` or `? <error>`, followed by an empty line
+fn batch(bot: &mut HexBot, input: impl BufRead, mut output: impl Write) -> std::io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match process_line(bot, &line) {
+            Ok(HexBotOutput::Empty) => write!(output, "=\n\n")?,
+            Ok(out) => write!(output, "= {out}\n\n")?,
+            Err(err) => write!(output, "? {err}\n\n")?,
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
 
-    let mut bot = HexBot::new(colour);
+fn repl(bot: &mut HexBot) -> Result<(), Error> {
     let mut rl = Editor::<()>::new()?;
     loop {
         let readline = rl.readline("");
@@ -158,7 +377,7 @@ fn main() -> Result<(), Error> {
                     continue;
                 }
                 rl.add_history_entry(line.as_str());
-                match process_line(&mut bot, &line) {
+                match process_line(bot, &line) {
                     Ok(HexBotOutput::Empty) => {}
                     Ok(out) => println!("{out}"),
                     Err(err) => eprintln!("{err}"),
@@ -173,3 +392,24 @@ fn main() -> Result<(), Error> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn batch_responses() {
+        let mut bot = HexBot::with_seed(Colour::Black, 1);
+        let mut output = Vec::new();
+        batch(
+            &mut bot,
+            "init_board 3\n\nseto b2\nseto\ncheck_win\nwinning_path\nfoo\n".as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "=\n\n=\n\n? empty move\n\n= 0\n\n=\n\n? invalid command\n\n"
+        );
+    }
+}
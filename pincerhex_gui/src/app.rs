@@ -1,12 +1,17 @@
-use alloc::fmt::format;
+use alloc::{
+    fmt::format,
+    string::{String, ToString},
+    vec::Vec,
+};
 
 use eframe::{egui, App};
 use egui::{Align, Layout};
 
-use pincerhex_core::{first_move, PotentialEvaluator, Rand};
-use pincerhex_state::{State, Winner};
+use pincerhex_bot::EngineKind;
+use pincerhex_core::{first_move, EvalParams, Rand};
+use pincerhex_state::{notation, sgf::Sgf, State, SwapKind, Winner};
 
-use crate::board::{hex_border, hexagon, Piece};
+use crate::board::{hex_border, hexagon, winning_path, Engine, Piece};
 #[cfg(debug_assertions)]
 use crate::frame_history::FrameHistory;
 use crate::{
@@ -25,10 +30,21 @@ pub struct PincerhexApp {
     move_count: u16,
     state: PincerhexState,
     active: Piece,
+    engine: Engine,
+    swap_rule: bool,
+    /// Play the swap by mirroring the opening stone instead of switching colours
+    swap_pieces: bool,
+    swapped: bool,
 
     #[serde(skip)]
     won: Option<bool>,
 
+    /// SGF record shown for copying, or a record or move list pasted for loading
+    #[serde(skip)]
+    sgf: String,
+    #[serde(skip)]
+    sgf_error: Option<String>,
+
     #[serde(skip)]
     #[cfg(debug_assertions)]
     frame_history: crate::frame_history::FrameHistory,
@@ -46,10 +62,16 @@ impl Default for PincerhexApp {
             player_is_white: true,
             new_game: true,
             won: None,
+            sgf: String::new(),
+            sgf_error: None,
             #[cfg(debug_assertions)]
             frame_history: FrameHistory::default(),
             move_count: 0,
             active: Piece::White,
+            engine: Engine::Potential,
+            swap_rule: true,
+            swap_pieces: false,
+            swapped: false,
             state,
             rng: Rng::default(),
         }
@@ -143,8 +165,29 @@ impl PincerhexApp {
                 &mut self.player_is_white,
                 format(format_args!("Player colour: {label}")),
             ));
+            egui::ComboBox::from_label("Engine")
+                .selected_text(format(format_args!("{}", EngineKind::from(self.engine))))
+                .show_ui(ui, |ui| {
+                    for kind in EngineKind::ALL {
+                        ui.selectable_value(
+                            &mut self.engine,
+                            kind.into(),
+                            format(format_args!("{kind}")),
+                        );
+                    }
+                });
+            ui.checkbox(&mut self.swap_rule, "Swap rule");
+            if self.swap_rule {
+                ui.checkbox(&mut self.swap_pieces, "Swap pieces");
+            }
             if ui.add(egui::Button::new("Start game")).clicked() {
                 self.new_game = false;
+                self.state.0.set_swap_rule(self.swap_rule);
+                self.state.0.set_swap_kind(if self.swap_pieces {
+                    SwapKind::Pieces
+                } else {
+                    SwapKind::Sides
+                });
                 self.active = if self.player_is_white {
                     Piece::White
                 } else {
@@ -153,9 +196,148 @@ impl PincerhexApp {
                     Piece::Black
                 };
             }
+            self.sgf_controls(ui);
         });
     }
 
+    fn sgf_controls(&mut self, ui: &mut egui::Ui) {
+        if !self.new_game && ui.button("Copy SGF").clicked() {
+            match Sgf::from_state(&self.state.0) {
+                Ok(record) => {
+                    self.sgf = record.to_string();
+                    let sgf = self.sgf.clone();
+                    ui.output_mut(|o| o.copied_text = sgf);
+                    self.sgf_error = None;
+                }
+                Err(err) => self.sgf_error = Some(err.to_string()),
+            }
+        }
+        ui.add(
+            egui::TextEdit::multiline(&mut self.sgf)
+                .hint_text("Paste an SGF record, HexWorld URL or move list"),
+        );
+        if ui.button("Load game").clicked() {
+            self.load_game();
+        }
+        if let Some(err) = &self.sgf_error {
+            ui.label(err.as_str());
+        }
+    }
+
+    /// Continues the game from the pasted record, with the player to move
+    fn load_game(&mut self) {
+        use pincerhex_core::{Colour, Move};
+        let text = self.sgf.trim();
+        let state = if text.starts_with('(') {
+            text.parse::<Sgf>()
+                .and_then(|sgf| sgf.to_state())
+                .map_err(|e| e.to_string())
+        } else if text.contains('#') {
+            notation::from_hexworld(text).map_err(|e| e.to_string())
+        } else {
+            let size = self.state.0.get_board().size;
+            notation::from_little_golem(text, size).map_err(|e| e.to_string())
+        };
+        let mut state = match state {
+            Ok(state) => state,
+            Err(err) => {
+                self.sgf_error = Some(err);
+                return;
+            }
+        };
+        state.set_swap_rule(self.swap_rule);
+        let history = state.history();
+        let moves = history
+            .iter()
+            .filter(|e| matches!(e.mv, Move::Move(_)))
+            .count();
+        self.move_count = u16::try_from(moves).unwrap_or(u16::MAX);
+        self.swapped = history.iter().any(|e| e.mv == Move::Swap);
+        self.active = state.active().into();
+        self.player_is_white = state.active() == Colour::White;
+        self.won = state.check_win().map(|c| c == state.active());
+        self.state = PincerhexState(state);
+        self.new_game = false;
+        self.sgf_error = None;
+    }
+
+    /// Plays the swap rule for whoever is to play
+    fn swap(&mut self) {
+        self.state.0.swap().expect("valid swap");
+        if self.state.0.swap_kind() == SwapKind::Sides {
+            self.player_is_white = !self.player_is_white;
+        }
+        self.active = self.state.0.active().into();
+        self.swapped = true;
+    }
+
+    fn player_colour(&self) -> pincerhex_core::Colour {
+        if self.player_is_white {
+            Piece::White.into()
+        } else {
+            Piece::Black.into()
+        }
+    }
+
+    fn can_undo(&self) -> bool {
+        use pincerhex_core::PieceState;
+        let history = self.state.0.history();
+        // Turns alternate, so the last two moves always include one of the player's
+        history.len() >= 2
+            || history
+                .first()
+                .is_some_and(|e| e.piece == PieceState::Colour(self.player_colour()))
+    }
+
+    /// Takes back moves up to and including the player's last one
+    fn undo(&mut self) {
+        use pincerhex_core::{Move, PieceState};
+        while let Some(entry) = self.state.0.undo() {
+            let by_player = match entry.mv {
+                Move::Swap => {
+                    if entry.swap_kind() == Some(SwapKind::Sides) {
+                        self.player_is_white = !self.player_is_white;
+                    }
+                    self.active = entry.to_play().into();
+                    self.swapped = false;
+                    entry.to_play() == self.player_colour()
+                }
+                Move::Move(_) => {
+                    self.move_count -= 1;
+                    if let PieceState::Colour(c) = entry.piece {
+                        self.active = c.into();
+                    }
+                    entry.piece == PieceState::Colour(self.player_colour())
+                }
+            };
+            if by_player {
+                break;
+            }
+        }
+        self.won = None;
+    }
+
+    fn can_swap(&self) -> bool {
+        self.won.is_none() && self.move_count == 1 && self.swap_rule && !self.swapped
+    }
+
+    fn bot_move(&mut self, frame: &mut eframe::Frame) {
+        use pincerhex_core::Tile;
+        let mv = EngineKind::from(self.engine)
+            .engine(EvalParams::default())
+            .best_move(
+                self.state.0.get_board(),
+                self.active.into(),
+                self.active.into(),
+                self.move_count,
+                &mut self.rng,
+            )
+            .mv;
+        if let Tile::Regular(i, j) = mv {
+            self.place_piece((i, j), self.active, Some(frame));
+        }
+    }
+
     fn restart(&mut self) {
         *self = Self::default();
     }
@@ -166,6 +348,14 @@ impl PincerhexApp {
             if ui.button("New Game").clicked() {
                 self.restart()
             }
+            if self.can_swap() && ui.button("Swap").clicked() {
+                self.swap();
+                self.bot_move(frame);
+            }
+            if self.can_undo() && ui.button("Undo").clicked() {
+                self.undo();
+            }
+            self.sgf_controls(ui);
             ui.label(match (self.won, self.move_count, self.player_is_white) {
                 (Some(true), _, _) => "You won!",
                 (Some(false), _, _) => "You lost!",
@@ -218,18 +408,26 @@ impl PincerhexApp {
             }
         });
 
+        if let Some((_, path)) = self.state.0.winning_path() {
+            let centres = path
+                .iter()
+                .filter_map(|t| match *t {
+                    Tile::Regular(x, y) => Some(start + next_y * y as f32 + next_x * x as f32),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            winning_path(ui, &centres, dimensions.hex_size / 2.);
+        }
+
         if let Some(mv) = clicked {
             if self.place_piece(mv, self.active, Some(frame)) {
                 return;
             }
-            let mv = PotentialEvaluator::new(
-                self.state.0.get_board(),
-                self.active.into(),
-                self.active.into(),
-            )
-            .evaluate()
-            .get_best_move(self.move_count, &mut self.rng);
-            self.place_piece(mv, self.active, Some(frame));
+            if self.can_swap() && self.state.0.should_swap(&mut self.rng) {
+                self.swap();
+                return;
+            }
+            self.bot_move(frame);
         }
     }
 }
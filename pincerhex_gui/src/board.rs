@@ -30,6 +30,36 @@ impl From<pincerhex_core::Colour> for Piece {
     }
 }
 
+#[derive(serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq)]
+pub enum Engine {
+    Potential,
+    Mcts,
+    MctsGuided,
+    AlphaBeta,
+}
+
+impl From<Engine> for pincerhex_bot::EngineKind {
+    fn from(value: Engine) -> Self {
+        match value {
+            Engine::Potential => Self::Potential,
+            Engine::Mcts => Self::Mcts,
+            Engine::MctsGuided => Self::MctsGuided,
+            Engine::AlphaBeta => Self::AlphaBeta,
+        }
+    }
+}
+
+impl From<pincerhex_bot::EngineKind> for Engine {
+    fn from(value: pincerhex_bot::EngineKind) -> Self {
+        match value {
+            pincerhex_bot::EngineKind::Potential => Self::Potential,
+            pincerhex_bot::EngineKind::Mcts => Self::Mcts,
+            pincerhex_bot::EngineKind::MctsGuided => Self::MctsGuided,
+            pincerhex_bot::EngineKind::AlphaBeta => Self::AlphaBeta,
+        }
+    }
+}
+
 impl Piece {
     pub fn other(&self) -> Self {
         match self {
@@ -167,3 +197,13 @@ pub fn piece(ui: &mut egui::Ui, center: Pos2, radius: f32, colour: Piece) {
         Stroke::new(1.5, Color32::DARK_GRAY),
     );
 }
+
+/// Highlights a winning chain by ringing its stones and joining their centres
+pub fn winning_path(ui: &mut egui::Ui, centres: &[Pos2], radius: f32) {
+    let stroke = Stroke::new(3., Color32::GOLD);
+    for &centre in centres {
+        ui.painter().circle_stroke(centre, radius / 1.5, stroke);
+    }
+    ui.painter()
+        .add(epaint::Shape::line(centres.to_vec(), stroke));
+}
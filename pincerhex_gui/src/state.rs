@@ -1,6 +1,6 @@
 use alloc::vec::Vec;
 
-use pincerhex_state::State;
+use pincerhex_state::{State, SwapKind};
 
 use crate::board::Piece;
 
@@ -11,18 +11,71 @@ struct SerializedState {
     active: Piece,
     pieces: Vec<(i8, i8, Piece)>,
     size: i8,
+    /// Move history, replayed on load so SGF export and undo keep working. Missing from saves
+    /// made before it was stored.
+    #[serde(default)]
+    moves: Vec<SerializedMove>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+enum SerializedMove {
+    /// A stone placed on a tile, or the tile cleared for `None`
+    Place(i8, i8, Option<Piece>),
+    Swap {
+        to_play: Piece,
+        pieces: bool,
+    },
+}
+
+impl SerializedState {
+    /// Plays the saved moves on a new board, `None` if they don't give the saved stones
+    fn replay(&self) -> Option<State> {
+        use pincerhex_core::{PieceState, Tile};
+        let mut state = State::new(self.size);
+        for mv in &self.moves {
+            match *mv {
+                SerializedMove::Place(r, c, piece) => {
+                    let piece = piece.map_or(PieceState::Empty, |p| PieceState::Colour(p.into()));
+                    state.place_piece(Tile::Regular(r, c), piece).ok()?;
+                }
+                SerializedMove::Swap { to_play, pieces } => {
+                    state.set_to_play(to_play.into());
+                    state.set_swap_kind(if pieces {
+                        SwapKind::Pieces
+                    } else {
+                        SwapKind::Sides
+                    });
+                    state.swap().ok()?;
+                }
+            }
+        }
+        let stones = state
+            .get_board()
+            .iter()
+            .filter(|(_, s)| matches!(s, PieceState::Colour(_)))
+            .count();
+        let matches = stones == self.pieces.len()
+            && self.pieces.iter().all(|&(r, c, colour)| {
+                state.get_board().get(r, c) == Some(PieceState::Colour(colour.into()))
+            });
+        matches.then_some(state)
+    }
 }
 
 impl From<SerializedState> for PincerhexState {
     fn from(value: SerializedState) -> Self {
-        use pincerhex_core::{PieceState, Tile};
-        let mut state = State::new(value.size);
+        use pincerhex_core::{Board, PieceState, Tile};
+        let mut state = value.replay().unwrap_or_else(|| {
+            // Older saves only have the stones, which are restored without a history
+            let mut board = Board::new(value.size);
+            for &(r, c, colour) in value.pieces.iter() {
+                board
+                    .set_tile(Tile::Regular(r, c), PieceState::Colour(colour.into()))
+                    .unwrap();
+            }
+            State::from(board)
+        });
         state.set_to_play(value.active.into());
-        for &(r, c, colour) in value.pieces.iter() {
-            state
-                .place_piece(Tile::Regular(r, c), PieceState::Colour(colour.into()))
-                .unwrap();
-        }
         Self(state)
     }
 }
@@ -32,7 +85,7 @@ impl serde::Serialize for PincerhexState {
     where
         S: serde::Serializer,
     {
-        use pincerhex_core::{PieceState, Tile};
+        use pincerhex_core::{Move, PieceState, Tile};
         let board = self.0.get_board();
         let active: Piece = self.0.active().into();
         let mut pieces = Vec::new();
@@ -41,11 +94,30 @@ impl serde::Serialize for PincerhexState {
                 pieces.push((r, c, colour.into()));
             }
         }
+        let moves = self
+            .0
+            .history()
+            .iter()
+            .filter_map(|entry| match (entry.mv, entry.piece) {
+                (Move::Move(Tile::Regular(r, c)), PieceState::Colour(colour)) => {
+                    Some(SerializedMove::Place(r, c, Some(colour.into())))
+                }
+                (Move::Move(Tile::Regular(r, c)), PieceState::Empty) => {
+                    Some(SerializedMove::Place(r, c, None))
+                }
+                (Move::Move(_), _) => None,
+                (Move::Swap, _) => Some(SerializedMove::Swap {
+                    to_play: entry.to_play().into(),
+                    pieces: entry.swap_kind() == Some(SwapKind::Pieces),
+                }),
+            })
+            .collect();
 
         SerializedState {
             active,
             pieces,
             size: board.size,
+            moves,
         }
         .serialize(serializer)
     }
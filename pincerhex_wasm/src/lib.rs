@@ -2,13 +2,18 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
 #![allow(clippy::cast_sign_loss)]
 extern crate alloc;
-use alloc::string::String;
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 use rand::{rngs::SmallRng, Rng, SeedableRng};
 
+use pincerhex_bot::EngineKind;
 use pincerhex_core::{
-    first_move, should_swap, Board, Colour, PieceState, PotentialEvaluator, Tile,
+    first_move, should_swap, Bitboard, Board, Colour, EvalParams, PieceState, Tile,
 };
-use wasm_bindgen::prelude::wasm_bindgen;
+use pincerhex_state::{State, SwapKind};
+use wasm_bindgen::{prelude::wasm_bindgen, JsError};
 
 enum Move {
     Regular((i8, i8)),
@@ -49,7 +54,7 @@ const fn get_bot_colour(bot_is_white: bool) -> Colour {
 ///
 /// # Arguments
 ///
-/// * `size` - Size of the board from 1 to 26. Recommended size is 10.
+/// * `size` - Size of the board from 1 to 32. Recommended size is 10.
 #[wasm_bindgen]
 #[must_use]
 pub fn get_board(size: i8) -> String {
@@ -61,7 +66,7 @@ pub fn get_board(size: i8) -> String {
 /// # Arguments
 ///
 /// * `bot_is_white` - Whether the bot starts as white.
-/// * `size` - Size of the board from 1 to 26. Recommended size is 10.
+/// * `size` - Size of the board from 1 to 32. Recommended size is 10.
 /// * `seed` - 64-bit seed used for random number generation.
 #[wasm_bindgen]
 #[must_use]
@@ -76,8 +81,61 @@ pub fn get_first_move(bot_is_white: bool, size: i8, seed: u64) -> String {
     board.get_compressed()
 }
 
-/// Play a move. Since we want this library to be stateless all state has to be passed to this
-/// function.
+/// Plays the swap rule on a board after the opening move. Returns the string representation of
+/// the board.
+///
+/// # Arguments
+///
+/// * `board` - String representation of the board.
+/// * `swap_pieces` - Whether the opening stone is mirrored and changes colour. Otherwise the
+/// players switch colours and the board stays the same.
+///
+/// # Errors
+///
+/// Returns an error if `board` is malformed or could not be mirrored.
+#[wasm_bindgen]
+pub fn swap_board(board: &str, swap_pieces: bool) -> Result<String, JsError> {
+    let board = Board::try_from(board).map_err(|e| JsError::new(&e.to_string()))?;
+    let mut state = State::from(board);
+    state.set_swap_kind(if swap_pieces {
+        SwapKind::Pieces
+    } else {
+        SwapKind::Sides
+    });
+    state.swap().map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(state.get_compressed())
+}
+
+/// Returns the shortest chain of stones connecting the winner's edges.
+///
+/// The chain starts from the winner's first edge and is empty if nobody has won. Every tile is a
+/// 16-bit number with the column index in the LSB and the row index in the MSB.
+///
+/// # Arguments
+///
+/// * `board` - String representation of the board.
+///
+/// # Errors
+///
+/// Returns an error if `board` is malformed.
+#[wasm_bindgen]
+pub fn winning_path(board: &str) -> Result<Vec<u16>, JsError> {
+    let board = Board::try_from(board).map_err(|e| JsError::new(&e.to_string()))?;
+    let path = Bitboard::from(&board)
+        .winning_path()
+        .map(|(_, path)| path)
+        .unwrap_or_default();
+    Ok(path
+        .into_iter()
+        .filter_map(|tile| match tile {
+            Tile::Regular(r, c) => Some(((r as u16) << 8) | (c as u16)),
+            Tile::Edge1 | Tile::Edge2 | Tile::Invalid => None,
+        })
+        .collect())
+}
+
+/// Play a move with the default engine, the potential evaluator, and the swap rule on. Since we
+/// want this library to be stateless all state has to be passed to this function.
 ///
 /// # Arguments
 ///
@@ -87,10 +145,13 @@ pub fn get_first_move(bot_is_white: bool, size: i8, seed: u64) -> String {
 /// the MSB.
 /// * `board` - String representation of the board. Can be received from `get_first_move` if
 /// the bot is starting or `get_board` if the player is starting.
-/// * `move_count` - Number of moves played in the game so far.
+/// * `move_count` - Number of stones placed in the game so far, not counting a swap.
 /// * `seed` - 64-bit seed used for random number generation.
+///
+/// # Errors
+///
+/// Returns an error if `board` is malformed.
 #[wasm_bindgen]
-#[must_use]
 pub fn pincerhex_move(
     bot_is_white: bool,
     bot_started_white: bool,
@@ -98,20 +159,87 @@ pub fn pincerhex_move(
     board: &str,
     move_count: u16,
     seed: u64,
-) -> u32 {
+) -> Result<u32, JsError> {
+    play(
+        bot_is_white,
+        bot_started_white,
+        player_move,
+        board,
+        move_count,
+        seed,
+        EngineKind::Potential,
+        true,
+    )
+}
+
+/// Same as `pincerhex_move`, with a choice of engine and swap rule.
+///
+/// # Arguments
+///
+/// * `engine` - Name of the engine used to pick the move, `potential`, `mcts`,
+/// `mcts-guided` or `alphabeta`.
+/// * `swap_rule` - Whether the bot may swap after the player's first move.
+///
+/// The other arguments are the same as for `pincerhex_move`.
+///
+/// # Errors
+///
+/// Returns an error if `engine` is not a known engine, `board` is malformed or the engine did
+/// not return a tile on the board.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn pincerhex_engine_move(
+    bot_is_white: bool,
+    bot_started_white: bool,
+    player_move: u16,
+    board: &str,
+    move_count: u16,
+    seed: u64,
+    engine: &str,
+    swap_rule: bool,
+) -> Result<u32, JsError> {
+    let engine = EngineKind::try_from(engine).map_err(|_| JsError::new("invalid engine"))?;
+    play(
+        bot_is_white,
+        bot_started_white,
+        player_move,
+        board,
+        move_count,
+        seed,
+        engine,
+        swap_rule,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn play(
+    bot_is_white: bool,
+    bot_started_white: bool,
+    player_move: u16,
+    board: &str,
+    move_count: u16,
+    seed: u64,
+    engine: EngineKind,
+    swap_rule: bool,
+) -> Result<u32, JsError> {
+    let mut engine = engine.engine(EvalParams::default());
     let mut rng = WasmRng(SmallRng::seed_from_u64(seed));
     let colour = get_bot_colour(bot_is_white);
     let starting = get_bot_colour(bot_started_white);
     let (r, c) = ((player_move >> 8) as i8, (player_move & 0xff) as i8);
-    let board = Board::from(board);
+    let board = Board::try_from(board).map_err(|e| JsError::new(&e.to_string()))?;
 
-    match move_count {
-        1 if should_swap(r, c, board.size, &mut rng) => Move::Swap,
-        count => Move::Regular(
-            PotentialEvaluator::new(&board, colour, starting)
-                .evaluate()
-                .get_best_move(count, &mut rng),
-        ),
+    Ok(match move_count {
+        1 if swap_rule && should_swap(r, c, board.size, &mut rng) => Move::Swap,
+        count => match engine
+            .best_move(&board, colour, starting, count, &mut rng)
+            .mv
+        {
+            Tile::Regular(r, c) => Move::Regular((r, c)),
+            Tile::Edge1 | Tile::Edge2 | Tile::Invalid => {
+                return Err(JsError::new("engine did not return a tile"))
+            }
+        },
     }
-    .into()
+    .into())
 }